@@ -0,0 +1,116 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::fs::{FileSystem, Node, NodeId, ROOT};
+
+/// Recreates `tree` under `target`, which has to be missing or empty. Files get their
+/// size with `set_len` and no data, which leaves them sparse on filesystems supporting
+/// it (and takes the full size on disk elsewhere).
+pub fn materialize(tree: &FileSystem, target: &Path) -> io::Result<()> {
+    if target.exists() && fs::read_dir(target)?.next().is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is not empty", target.display()),
+        ));
+    }
+    fs::create_dir_all(target)?;
+    for id in tree.ids() {
+        if id == ROOT {
+            continue;
+        }
+        let path = target.join(relative_path(tree, id)?);
+        match tree.node(id) {
+            Node::Dir(_) => fs::create_dir_all(&path)?,
+            Node::File(file) => fs::File::create(&path)?.set_len(file.size as u64)?,
+        }
+    }
+    return Ok(());
+}
+
+/// Path of `id` below the root, refusing names that would end up anywhere else.
+fn relative_path(tree: &FileSystem, id: NodeId) -> io::Result<PathBuf> {
+    let mut names = Vec::new();
+    let mut current = id;
+    while let Some(parent) = tree.parent(current) {
+        let name = tree.node(current).name();
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Can not create '{}' in {}", name, tree.path(parent)),
+            ));
+        }
+        names.push(name);
+        current = parent;
+    }
+    return Ok(names.iter().rev().collect());
+}
+
+/// Reads the tree under `root` from disk, sizes being the file lengths. Symbolic links
+/// (never followed) and names a transcript can't hold, those with spaces or not valid
+/// UTF-8, are left out and returned aside.
+pub fn scan(root: &Path) -> io::Result<(FileSystem, Vec<PathBuf>)> {
+    let mut tree = FileSystem::new();
+    let mut skipped = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), ROOT)];
+    while let Some((dir_path, dir)) = pending.pop() {
+        let mut entries = fs::read_dir(&dir_path)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let metadata = fs::symlink_metadata(entry.path())?;
+            let name = match entry.file_name().into_string() {
+                Ok(name) if !name.contains(char::is_whitespace) => name,
+                _ => {
+                    skipped.push(entry.path());
+                    continue;
+                }
+            };
+            if metadata.is_dir() {
+                pending.push((entry.path(), tree.add_dir(dir, &name)));
+            } else if metadata.is_file() {
+                tree.add_file(dir, &name, metadata.len() as usize);
+            } else {
+                skipped.push(entry.path());
+            }
+        }
+    }
+    return Ok((tree, skipped));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build_tree;
+    use crate::disk::*;
+
+    #[test]
+    fn test_materialize_and_scan() {
+        let target = std::env::temp_dir().join(format!("day_07_disk_{}", std::process::id()));
+        let tree = build_tree("./data_sample.txt", false);
+        materialize(&tree, &target).unwrap();
+        assert_eq!(fs::metadata(target.join("d/k")).unwrap().len(), 7214296);
+        assert_eq!(
+            materialize(&tree, &target).unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+
+        let (scanned, skipped) = scan(&target).unwrap();
+        fs::remove_dir_all(&target).unwrap();
+        assert_eq!(skipped, Vec::<PathBuf>::new());
+        let entries = |tree: &FileSystem| {
+            let sizes = tree.sizes();
+            let mut entries = tree
+                .ids()
+                .map(|id| (tree.path(id), sizes[id]))
+                .collect::<Vec<_>>();
+            entries.sort();
+            return entries;
+        };
+        assert_eq!(entries(&scanned), entries(&tree));
+
+        let mut evil = FileSystem::new();
+        evil.add_dir(ROOT, "..");
+        let target = std::env::temp_dir().join(format!("day_07_evil_{}", std::process::id()));
+        assert!(materialize(&evil, &target).is_err());
+        fs::remove_dir_all(&target).unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+use crate::fs::{FileSystem, NodeId};
+use utils::glob::Glob;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Less,
+    AtMost,
+    Equal,
+    AtLeast,
+    Greater,
+}
+
+/// Size condition like `>100k`, `<=2M` or `584` (exactly), units being powers of 1024.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizePredicate {
+    pub comparison: Comparison,
+    pub size: usize,
+}
+
+impl SizePredicate {
+    pub fn parse(text: &str) -> Option<Self> {
+        let operators = [
+            ("<=", Comparison::AtMost),
+            (">=", Comparison::AtLeast),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+            ("=", Comparison::Equal),
+        ];
+        let (comparison, number) = operators
+            .iter()
+            .find_map(|&(operator, comparison)| Some((comparison, text.strip_prefix(operator)?)))
+            .unwrap_or((Comparison::Equal, text));
+
+        let units = ['k', 'm', 'g', 't'];
+        let (number, power) = match number.chars().last()?.to_ascii_lowercase() {
+            unit if units.contains(&unit) => (
+                &number[..number.len() - 1],
+                units.iter().position(|&u| u == unit)? as u32 + 1,
+            ),
+            _ => (number, 0),
+        };
+        let size = number
+            .parse::<usize>()
+            .ok()?
+            .checked_mul(1024_usize.pow(power))?;
+        return Some(Self { comparison, size });
+    }
+
+    pub fn matches(&self, size: usize) -> bool {
+        return match self.comparison {
+            Comparison::Less => size < self.size,
+            Comparison::AtMost => size <= self.size,
+            Comparison::Equal => size == self.size,
+            Comparison::AtLeast => size >= self.size,
+            Comparison::Greater => size > self.size,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeType {
+    File,
+    Dir,
+}
+
+impl NodeType {
+    pub fn parse(name: &str) -> Option<Self> {
+        return match name {
+            "f" | "file" => Some(NodeType::File),
+            "d" | "dir" => Some(NodeType::Dir),
+            _ => None,
+        };
+    }
+}
+
+/// `find` like query, every part given has to match. Patterns without a `/` are
+/// matched against the names, the others against the whole paths.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub pattern: Option<Glob>,
+    pub name_only: bool,
+    pub sizes: Vec<SizePredicate>,
+    pub node_type: Option<NodeType>,
+}
+
+impl Query {
+    /// Matches paths or names against `pattern`, `None` if it is not a valid glob.
+    pub fn with_pattern(mut self, pattern: &str) -> Option<Self> {
+        self.pattern = Some(Glob::parse(pattern)?);
+        self.name_only = !pattern.contains('/');
+        return Some(self);
+    }
+}
+
+/// `(node, size)` of everything matching `query`, sorted by path.
+pub fn find(fs: &FileSystem, query: &Query) -> Vec<(NodeId, usize)> {
+    let sizes = fs.sizes();
+    let mut found = fs
+        .ids()
+        .filter(|&id| {
+            let node = fs.node(id);
+            let type_matches = match query.node_type {
+                Some(NodeType::Dir) => node.is_dir(),
+                Some(NodeType::File) => !node.is_dir(),
+                None => true,
+            };
+            let pattern_matches = match &query.pattern {
+                Some(glob) if query.name_only => glob.is_match(node.name()),
+                Some(glob) => glob.is_match(&fs.path(id)),
+                None => true,
+            };
+            return type_matches
+                && pattern_matches
+                && query.sizes.iter().all(|size| size.matches(sizes[id]));
+        })
+        .map(|id| (fs.path(id), id, sizes[id]))
+        .collect::<Vec<_>>();
+    found.sort();
+    return found.into_iter().map(|(_, id, size)| (id, size)).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build_tree;
+    use crate::find::*;
+
+    #[test]
+    fn test_find() {
+        assert_eq!(
+            SizePredicate::parse(">100k"),
+            Some(SizePredicate {
+                comparison: Comparison::Greater,
+                size: 102400
+            })
+        );
+        assert_eq!(SizePredicate::parse("<=2M").unwrap().size, 2 * 1024 * 1024);
+        assert_eq!(
+            SizePredicate::parse("584").unwrap().comparison,
+            Comparison::Equal
+        );
+        assert_eq!(SizePredicate::parse(">x"), None);
+
+        let fs = build_tree("./data_sample.txt", false);
+        let paths = |query: &Query| {
+            return find(&fs, query)
+                .iter()
+                .map(|&(id, _)| fs.path(id))
+                .collect::<Vec<String>>();
+        };
+        let query = Query::default().with_pattern("/**/*.*").unwrap();
+        assert_eq!(
+            paths(&query),
+            ["/a/h.lst", "/b.txt", "/c.dat", "/d/d.ext", "/d/d.log"]
+        );
+        let query = Query {
+            sizes: vec![SizePredicate::parse(">=8M").unwrap()],
+            ..query
+        };
+        assert_eq!(paths(&query), ["/b.txt", "/c.dat"]);
+
+        let query = Query {
+            node_type: Some(NodeType::Dir),
+            sizes: vec![SizePredicate::parse("<1m").unwrap()],
+            ..Query::default()
+        };
+        assert_eq!(paths(&query), ["/a", "/a/e"]);
+        let query = Query::default().with_pattern("?").unwrap();
+        assert_eq!(
+            paths(&query),
+            ["/a", "/a/e", "/a/e/i", "/a/f", "/a/g", "/d", "/d/j", "/d/k"]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+/// Index of a node in its `FileSystem`.
+pub type NodeId = usize;
+
+pub const ROOT: NodeId = 0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dir {
+    pub name: String,
+    /// `None` only for the root
+    pub parent: Option<NodeId>,
+    /// name -> node, sorted by name
+    pub children: BTreeMap<String, NodeId>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct File {
+    pub name: String,
+    pub parent: NodeId,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Dir(Dir),
+    File(File),
+}
+
+impl Node {
+    pub fn name(&self) -> &str {
+        return match self {
+            Node::Dir(dir) => &dir.name,
+            Node::File(file) => &file.name,
+        };
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        return match self {
+            Node::Dir(dir) => dir.parent,
+            Node::File(file) => Some(file.parent),
+        };
+    }
+
+    pub fn is_dir(&self) -> bool {
+        return matches!(self, Node::Dir(_));
+    }
+}
+
+/// Directory tree, nodes are only ever added so children always come after their parent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl FileSystem {
+    /// Just the root directory, `/`.
+    pub fn new() -> Self {
+        return Self {
+            nodes: vec![Node::Dir(Dir {
+                name: String::from("/"),
+                parent: None,
+                children: BTreeMap::new(),
+            })],
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.nodes.len();
+    }
+
+    /// Never, there is always the root.
+    pub fn is_empty(&self) -> bool {
+        return false;
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        return &self.nodes[id];
+    }
+
+    pub fn dir(&self, id: NodeId) -> &Dir {
+        return match &self.nodes[id] {
+            Node::Dir(dir) => dir,
+            Node::File(_) => panic!("{} is a file, not a directory", self.path(id)),
+        };
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        return self.nodes[id].parent();
+    }
+
+    pub fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        return self.dir(dir).children.get(name).copied();
+    }
+
+    /// Directory `name` in `parent`, created if missing.
+    pub fn add_dir(&mut self, parent: NodeId, name: &str) -> NodeId {
+        if let Some(id) = self.child(parent, name) {
+            if !self.nodes[id].is_dir() {
+                panic!("{} is a file, not a directory", self.path(id));
+            }
+            return id;
+        }
+        return self.add_node(
+            parent,
+            Node::Dir(Dir {
+                name: name.to_string(),
+                parent: Some(parent),
+                children: BTreeMap::new(),
+            }),
+        );
+    }
+
+    /// File `name` in `parent`, listing it again only updates its size.
+    pub fn add_file(&mut self, parent: NodeId, name: &str, size: usize) -> NodeId {
+        if let Some(id) = self.child(parent, name) {
+            match &mut self.nodes[id] {
+                Node::File(file) => file.size = size,
+                Node::Dir(_) => panic!("{} is a directory, not a file", self.path(id)),
+            }
+            return id;
+        }
+        return self.add_node(
+            parent,
+            Node::File(File {
+                name: name.to_string(),
+                parent,
+                size,
+            }),
+        );
+    }
+
+    fn add_node(&mut self, parent: NodeId, node: Node) -> NodeId {
+        let id = self.nodes.len();
+        let name = node.name().to_string();
+        self.nodes.push(node);
+        match &mut self.nodes[parent] {
+            Node::Dir(dir) => dir.children.insert(name, id),
+            Node::File(_) => panic!("Can not add {} to a file", name),
+        };
+        return id;
+    }
+
+    /// Absolute path, e.g. `/a/e`.
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.parent(current) {
+            names.push(self.nodes[current].name());
+            current = parent;
+        }
+        names.reverse();
+        return format!("/{}", names.join("/"));
+    }
+
+    /// Node at an absolute path.
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        let mut current = ROOT;
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            current = match &self.nodes[current] {
+                Node::Dir(dir) => *dir.children.get(name)?,
+                Node::File(_) => return None,
+            };
+        }
+        return Some(current);
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        return 0..self.nodes.len();
+    }
+
+    pub fn dirs(&self) -> impl Iterator<Item = NodeId> + '_ {
+        return self.ids().filter(|&id| self.nodes[id].is_dir());
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &File> {
+        return self.nodes.iter().filter_map(|node| match node {
+            Node::File(file) => Some(file),
+            Node::Dir(_) => None,
+        });
+    }
+
+    /// Size of every node (files included) indexed by id, directories summing everything below.
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = self
+            .nodes
+            .iter()
+            .map(|node| match node {
+                Node::File(file) => file.size,
+                Node::Dir(_) => 0,
+            })
+            .collect::<Vec<usize>>();
+        // children come after their parents, so going backwards every node is complete
+        // before it is added to its parent
+        for id in (1..self.nodes.len()).rev() {
+            if let Some(parent) = self.parent(id) {
+                sizes[parent] += sizes[id];
+            }
+        }
+        return sizes;
+    }
+
+    /// `(directory, size)` of every directory.
+    pub fn dir_sizes(&self) -> Vec<(NodeId, usize)> {
+        let sizes = self.sizes();
+        return self.dirs().map(|id| (id, sizes[id])).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::*;
+
+    #[test]
+    fn test_tree() {
+        let mut fs = FileSystem::new();
+        let a = fs.add_dir(ROOT, "a");
+        let e = fs.add_dir(a, "e");
+        fs.add_file(e, "i", 584);
+        fs.add_file(a, "f", 29116);
+        fs.add_file(ROOT, "b.txt", 100);
+        assert_eq!(fs.add_dir(ROOT, "a"), a);
+        fs.add_file(e, "i", 584);
+
+        assert_eq!(fs.path(e), "/a/e");
+        assert_eq!(fs.path(ROOT), "/");
+        assert_eq!(
+            fs.lookup("/a/e/i").map(|id| fs.path(id)),
+            Some(String::from("/a/e/i"))
+        );
+        assert_eq!(fs.lookup("/a/x"), None);
+        let sizes = fs.sizes();
+        assert_eq!(
+            (sizes[ROOT], sizes[a], sizes[e]),
+            (584 + 29116 + 100, 584 + 29116, 584)
+        );
+    }
+}
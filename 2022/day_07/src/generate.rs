@@ -0,0 +1,300 @@
+use std::collections::VecDeque;
+
+use crate::fs::{FileSystem, Node, NodeId, ROOT};
+use crate::shell::{PathPart, PathStart, ShellPath};
+use utils::random::Rng;
+
+/// Order in which a transcript explores the directories.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WalkOrder {
+    /// into every directory right after listing it, back out with `cd ..`
+    DepthFirst,
+    /// level by level, going through the closest common directory between two
+    BreadthFirst,
+    /// depth first, now and then going back to list a directory again
+    Revisits,
+}
+
+impl WalkOrder {
+    pub const ALL: [WalkOrder; 3] = [
+        WalkOrder::DepthFirst,
+        WalkOrder::BreadthFirst,
+        WalkOrder::Revisits,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        return match name {
+            "dfs" => Some(WalkOrder::DepthFirst),
+            "bfs" => Some(WalkOrder::BreadthFirst),
+            "revisit" | "revisits" => Some(WalkOrder::Revisits),
+            _ => None,
+        };
+    }
+}
+
+/// Tree with `dirs` directories besides the root and `files` files of up to `max_size`
+/// bytes, each added in a random directory.
+pub fn random_tree(rng: &mut Rng, dirs: usize, files: usize, max_size: usize) -> FileSystem {
+    let mut fs = FileSystem::new();
+    let mut all_dirs = vec![ROOT];
+    for _ in 0..dirs {
+        let parent = *rng.pick(&all_dirs);
+        let name = random_name(rng, &fs, parent, false);
+        all_dirs.push(fs.add_dir(parent, &name));
+    }
+    for _ in 0..files {
+        let parent = *rng.pick(&all_dirs);
+        let name = random_name(rng, &fs, parent, true);
+        fs.add_file(parent, &name, rng.between(1, max_size));
+    }
+    return fs;
+}
+
+/// Lowercase name not used in `parent` yet, files sometimes getting an extension.
+fn random_name(rng: &mut Rng, fs: &FileSystem, parent: NodeId, file: bool) -> String {
+    loop {
+        let length = rng.between(1, 8);
+        let mut name = (0..length)
+            .map(|_| (b'a' + rng.below(26) as u8) as char)
+            .collect::<String>();
+        if file && rng.chance(50) {
+            let extensions = [".txt", ".dat", ".lst", ".log"];
+            let extension = rng.pick(&extensions);
+            name.push_str(extension);
+        }
+        if fs.child(parent, &name).is_none() {
+            return name;
+        }
+    }
+}
+
+/// Reads a tree described like a single listing with absolute paths, `<size> <path>`
+/// for files and `dir <path>` for directories (only needed when empty). Directories on
+/// the way are created, `.` and `..` in paths being resolved first since no node of a
+/// transcript can be named after them. `#` starts a comment.
+pub fn tree_from_description(lines: impl Iterator<Item = String>) -> FileSystem {
+    let mut fs = FileSystem::new();
+    for (i, line) in lines.enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((kind, path)) = line.split_once(' ') else {
+            panic!("line {}: expected '<size> <path>' or 'dir <path>'", i + 1);
+        };
+        let mut names = Vec::new();
+        for name in path.split('/') {
+            match name {
+                "" | "." => {}
+                ".." => {
+                    names.pop();
+                }
+                name => names.push(name),
+            }
+        }
+        let name = if kind == "dir" { None } else { names.pop() };
+        if kind != "dir" && matches!(path.rsplit('/').next(), Some("." | "..")) {
+            panic!("line {}: '{}' is not a file name", i + 1, path);
+        }
+        let mut dir = ROOT;
+        for name in names {
+            dir = fs.add_dir(dir, name);
+        }
+        if let Some(name) = name {
+            let size = kind
+                .parse::<usize>()
+                .unwrap_or_else(|_| panic!("line {}: invalid size '{}'", i + 1, kind));
+            fs.add_file(dir, name, size);
+        }
+    }
+    return fs;
+}
+
+/// `$ cd` and `$ ls` transcript exploring all of `fs`, listings in a random order.
+pub fn transcript(fs: &FileSystem, order: WalkOrder, rng: &mut Rng) -> Vec<String> {
+    let mut lines = vec![String::from("$ cd /")];
+    let mut listed = Vec::new();
+    match order {
+        WalkOrder::DepthFirst | WalkOrder::Revisits => {
+            walk_depth_first(fs, ROOT, order, rng, &mut listed, &mut lines);
+        }
+        WalkOrder::BreadthFirst => {
+            let mut pwd = ROOT;
+            let mut queue = VecDeque::from([ROOT]);
+            while let Some(dir) = queue.pop_front() {
+                navigate(fs, pwd, dir, &mut lines);
+                pwd = dir;
+                list(fs, dir, rng, &mut lines);
+                queue.extend(subdirs(fs, dir));
+            }
+        }
+    }
+    return lines;
+}
+
+fn walk_depth_first(
+    fs: &FileSystem,
+    dir: NodeId,
+    order: WalkOrder,
+    rng: &mut Rng,
+    listed: &mut Vec<NodeId>,
+    lines: &mut Vec<String>,
+) {
+    list(fs, dir, rng, lines);
+    listed.push(dir);
+    let mut children = subdirs(fs, dir);
+    rng.shuffle(&mut children);
+    for child in children {
+        lines.push(cd_into(fs.node(child).name()));
+        walk_depth_first(fs, child, order, rng, listed, lines);
+        lines.push(String::from("$ cd .."));
+
+        if order == WalkOrder::Revisits && rng.chance(30) {
+            let again = *rng.pick(listed);
+            navigate(fs, dir, again, lines);
+            list(fs, again, rng, lines);
+            navigate(fs, again, dir, lines);
+        }
+    }
+}
+
+fn subdirs(fs: &FileSystem, dir: NodeId) -> Vec<NodeId> {
+    return fs
+        .dir(dir)
+        .children
+        .values()
+        .copied()
+        .filter(|&id| fs.node(id).is_dir())
+        .collect();
+}
+
+fn list(fs: &FileSystem, dir: NodeId, rng: &mut Rng, lines: &mut Vec<String>) {
+    lines.push(String::from("$ ls"));
+    let mut entries = fs
+        .dir(dir)
+        .children
+        .values()
+        .map(|&id| match fs.node(id) {
+            Node::Dir(dir) => format!("dir {}", dir.name),
+            Node::File(file) => format!("{} {}", file.size, file.name),
+        })
+        .collect::<Vec<String>>();
+    rng.shuffle(&mut entries);
+    lines.extend(entries);
+}
+
+/// `$ cd` lines from `from` to `to`, up to the closest directory they are both in and back
+/// down, jumping to `/` instead of going up more than once to it.
+fn navigate(fs: &FileSystem, from: NodeId, to: NodeId, lines: &mut Vec<String>) {
+    let ancestors = |mut id: NodeId| {
+        let mut path = vec![id];
+        while let Some(parent) = fs.parent(id) {
+            path.push(parent);
+            id = parent;
+        }
+        path.reverse();
+        return path;
+    };
+    let (from_path, to_path) = (ancestors(from), ancestors(to));
+    let common = from_path
+        .iter()
+        .zip(&to_path)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 1 && from_path.len() > 2 {
+        lines.push(String::from("$ cd /"));
+    } else {
+        lines.extend((common..from_path.len()).map(|_| String::from("$ cd ..")));
+    }
+    for &dir in &to_path[common..] {
+        lines.push(cd_into(fs.node(dir).name()));
+    }
+}
+
+/// `$ cd <name>`, going through `./` for names that would mean something else alone,
+/// like `~` or options starting with `-`.
+fn cd_into(name: &str) -> String {
+    let child = ShellPath {
+        start: PathStart::Pwd,
+        parts: vec![PathPart::Name(String::from(name))],
+    };
+    if name.starts_with('-') || ShellPath::parse(name).as_ref() != Some(&child) {
+        return format!("$ cd ./{}", name);
+    }
+    return format!("$ cd {}", name);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build_tree_from;
+    use crate::generate::*;
+
+    #[test]
+    fn test_transcripts() {
+        let description = "\
+            dir /empty
+            584 /a/e/i
+            29116 /a/f  # a comment
+            14848514 /b.txt
+        ";
+        let fs = tree_from_description(description.lines().map(String::from));
+        assert_eq!(fs.sizes()[ROOT], 584 + 29116 + 14848514);
+        assert!(fs.lookup("/empty").is_some_and(|id| fs.node(id).is_dir()));
+
+        let mut rng = Rng::new(7);
+        let lines = transcript(&fs, WalkOrder::BreadthFirst, &mut rng);
+        let cds = lines
+            .iter()
+            .filter(|line| line.starts_with("$ cd"))
+            .map(|line| line.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            cds,
+            [
+                "$ cd /",
+                "$ cd a",
+                "$ cd ..",
+                "$ cd empty",
+                "$ cd ..",
+                "$ cd a",
+                "$ cd e"
+            ]
+        );
+
+        for order in WalkOrder::ALL {
+            let lines = transcript(&fs, order, &mut rng);
+            assert_eq!(
+                build_tree_from(lines.into_iter(), false).sizes()[ROOT],
+                fs.sizes()[ROOT]
+            );
+        }
+
+        let fs = tree_from_description(["5 /~/-x/f".to_string()].into_iter());
+        for order in WalkOrder::ALL {
+            let lines = transcript(&fs, order, &mut rng);
+            assert!(lines.contains(&String::from("$ cd ./~")));
+            let rebuilt = build_tree_from(lines.into_iter(), false);
+            let f = rebuilt.lookup("/~/-x/f").unwrap();
+            assert_eq!(rebuilt.sizes()[f], 5);
+            assert_eq!(rebuilt.dir(ROOT).children.len(), 1);
+        }
+
+        let description = "\
+            5 /a/../f
+            dir /b/./c/
+            3 /b/c/../../../g
+        ";
+        let fs = tree_from_description(description.lines().map(String::from));
+        let paths = |fs: &FileSystem| {
+            let mut paths = fs.ids().map(|id| fs.path(id)).collect::<Vec<String>>();
+            paths.sort();
+            return paths;
+        };
+        assert_eq!(paths(&fs), ["/", "/b", "/b/c", "/f", "/g"]);
+        for order in WalkOrder::ALL {
+            let rebuilt = build_tree_from(transcript(&fs, order, &mut rng).into_iter(), false);
+            assert_eq!(paths(&rebuilt), paths(&fs));
+            assert_eq!(rebuilt.sizes()[ROOT], 8);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+pub mod disk;
+pub mod find;
+pub mod fs;
+pub mod generate;
+pub mod plan;
+pub mod report;
+pub mod shell;
+pub mod validate;
+
+use fs::FileSystem;
+use shell::{LongEntry, Shell, ShellPath};
+use utils::io::yield_lines_trimmed;
+
+pub fn parse(line: &str, after_pwd: bool) -> Cmd {
+    return try_parse(line, after_pwd)
+        .unwrap_or_else(|| panic!("Unknown command or output: {}", line));
+}
+
+/// `None` for lines that are neither a known command nor the output of one. Paths are
+/// only taken as the output of `pwd` on the line `after_pwd`, not to mistake them for
+/// anything else.
+pub fn try_parse(line: &str, after_pwd: bool) -> Option<Cmd> {
+    if let Some(command) = line.strip_prefix("$ ") {
+        let args = command.split_whitespace().collect::<Vec<&str>>();
+        return match args.as_slice() {
+            ["cd"] => Some(Cmd::Cd {
+                path: ShellPath::parse("~")?,
+            }),
+            ["cd", path] => Some(Cmd::Cd {
+                path: ShellPath::parse(path)?,
+            }),
+            ["ls", args @ ..] => parse_ls(args),
+            ["pwd"] => Some(Cmd::Pwd),
+            _ => None,
+        };
+    }
+    if let Some(entry) = LongEntry::parse(line) {
+        return Some(Cmd::LsLongListed { entry });
+    }
+
+    let split = line.split(' ').collect::<Vec<&str>>();
+    return match split.as_slice() {
+        [path] if after_pwd && path.starts_with('/') => Some(Cmd::PwdOutput {
+            path: String::from(*path),
+        }),
+        ["total", blocks] => Some(Cmd::LsTotal {
+            blocks: blocks.parse::<usize>().ok()?,
+        }),
+        ["dir", name] => Some(Cmd::LsDirListed {
+            name: String::from(*name),
+        }),
+        [size, name] => Some(Cmd::LsFileListed {
+            name: String::from(*name),
+            size: size.parse::<usize>().ok()?,
+        }),
+        _ => None,
+    };
+}
+
+/// `ls [-flags] [path]`, only `-l` changing anything.
+fn parse_ls(args: &[&str]) -> Option<Cmd> {
+    let (flags, paths): (Vec<&str>, Vec<&str>) = args.iter().partition(|arg| arg.starts_with('-'));
+    let path = match paths.as_slice() {
+        [] => None,
+        [path] => Some(ShellPath::parse(path)?),
+        _ => return None,
+    };
+    return Some(Cmd::Ls {
+        path,
+        long: flags.iter().any(|flag| flag.contains('l')),
+    });
+}
+
+#[derive(Debug)]
+pub enum Cmd {
+    /// `cd` alone goes home
+    Cd {
+        path: ShellPath,
+    },
+    Ls {
+        path: Option<ShellPath>,
+        long: bool,
+    },
+    Pwd,
+    /// only checked against the working directory by `validate`, the `cd`s already moved it
+    PwdOutput {
+        path: String,
+    },
+    LsDirListed {
+        name: String,
+    },
+    LsFileListed {
+        name: String,
+        size: usize,
+    },
+    LsLongListed {
+        entry: LongEntry,
+    },
+    /// first line of `ls -l`, in blocks
+    LsTotal {
+        blocks: usize,
+    },
+}
+
+impl Cmd {
+    pub fn apply(&self, fs: &mut FileSystem, shell: &mut Shell) {
+        match &self {
+            // entering a directory that was not listed (yet) still finds it
+            Cmd::Cd { path } => shell.pwd = path.resolve(fs, shell.pwd),
+            Cmd::Ls { path, .. } => {
+                shell.listing = match path {
+                    Some(path) => path.resolve(fs, shell.pwd),
+                    None => shell.pwd,
+                };
+            }
+            Cmd::LsDirListed { name } => {
+                fs.add_dir(shell.listing, name);
+            }
+            Cmd::LsFileListed { name, size } => {
+                fs.add_file(shell.listing, name, *size);
+            }
+            Cmd::LsLongListed { entry } if entry.is_dir() => {
+                fs.add_dir(shell.listing, &entry.name);
+            }
+            Cmd::LsLongListed { entry } if entry.is_file() => {
+                fs.add_file(shell.listing, &entry.name, entry.size);
+            }
+            // links and devices take no space that counts
+            Cmd::LsLongListed { .. } => {}
+            Cmd::Pwd | Cmd::PwdOutput { .. } | Cmd::LsTotal { .. } => {}
+        };
+    }
+}
+
+/// Rebuilds the directory tree explored by the terminal transcript in `input_file`.
+pub fn build_tree(input_file: &str, verbose: bool) -> FileSystem {
+    return build_tree_from(yield_lines_trimmed(input_file), verbose);
+}
+
+pub fn build_tree_from(lines: impl Iterator<Item = String>, verbose: bool) -> FileSystem {
+    let mut fs = FileSystem::new();
+    let mut shell = Shell::new();
+    let mut after_pwd = false;
+
+    for line in lines {
+        let cmd = parse(&line, after_pwd);
+        after_pwd = matches!(cmd, Cmd::Pwd);
+        if verbose {
+            println!("{:?}", cmd);
+        }
+        cmd.apply(&mut fs, &mut shell);
+        if verbose {
+            println!("pwd {}", fs.path(shell.pwd));
+        }
+    }
+    return fs;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::ROOT;
+    use crate::*;
+
+    #[test]
+    fn test_richer_commands() {
+        let transcript = "\
+            $ cd /x/y
+            $ pwd
+            /x/y
+            $ ls -l ../z
+            total 16
+            drwxr-xr-x 2 elf elves 4096 Dec  1 09:00 w
+            -rw-r--r-- 1 elf elves 100 Dec  1 09:00 a.txt
+            lrwxrwxrwx 1 elf elves 5 Dec  1  2022 link -> a.txt
+            $ cd ../z/w/../../y/./
+            $ ls
+            20 b
+            $ cd ~
+            $ ls x/z/w
+            3 c
+            $ cd x/z/w
+            $ pwd
+            /x/z/w
+            $ ls
+            4 d";
+        let fs = build_tree_from(
+            transcript.lines().map(|line| line.trim().to_string()),
+            false,
+        );
+        let size = |path| fs.sizes()[fs.lookup(path).unwrap()];
+        assert_eq!(size("/x/z/a.txt"), 100);
+        assert_eq!(fs.lookup("/x/z/link"), None);
+        assert_eq!(size("/x/y/b"), 20);
+        assert_eq!(size("/x/z/w"), 3 + 4);
+        assert_eq!(fs.sizes()[ROOT], 127);
+
+        assert!(matches!(
+            parse("$ ls -la /a", false),
+            Cmd::Ls {
+                long: true,
+                path: Some(_)
+            }
+        ));
+        assert!(try_parse("$ ls a b", false).is_none());
+        assert!(try_parse("$ pwd now", false).is_none());
+        assert!(matches!(
+            try_parse("/x/z", true),
+            Some(Cmd::PwdOutput { .. })
+        ));
+        assert!(try_parse("/x/z", false).is_none());
+    }
+}
@@ -1,22 +1,261 @@
-use std::collections::HashMap;
-
-use utils::io::yield_lines_trimmed;
+use day_07::build_tree;
+use day_07::disk::{materialize, scan};
+use day_07::find::{find, NodeType, Query, SizePredicate};
+use day_07::fs::{FileSystem, NodeId, ROOT};
+use day_07::generate::{random_tree, transcript, tree_from_description, WalkOrder};
+use day_07::plan::{plan_deletion, Objective};
+use day_07::report::{largest, render_du, render_tree, SortBy};
+use day_07::validate::{validate_file, Mode};
+use std::path::Path;
+use utils::io::{flag_value, has_flag, yield_lines_trimmed};
+use utils::random::Rng;
 
 fn main() {
-    let (input_file, verbose) = utils::io::parse_args();
-    let dirs_sizes = calc_dirs_sizes(&input_file, verbose);
-    let part_one = solve_part_one(&dirs_sizes, 100000);
-    let part_two = solve_part_two(&dirs_sizes, 70000000, 30000000, verbose);
+    let (input_file, verbose, mode, mode_args) = utils::io::parse_args_with_mode();
+    if mode.as_deref() == Some("check") {
+        check_transcript(&input_file, &mode_args);
+        return;
+    }
+    if mode.as_deref() == Some("scan") {
+        print_scan(&input_file, &mode_args);
+        return;
+    }
+    if mode.as_deref() == Some("generate") {
+        print_transcript(&input_file, &mode_args);
+        return;
+    }
+    let fs = build_tree(&input_file, verbose);
+    match mode.as_deref() {
+        None => {}
+        Some("tree") => {
+            print_tree(&fs, &mode_args);
+            return;
+        }
+        Some("du") => {
+            let top = parse_count_flag(&mode_args, "top", 10);
+            let human = has_flag(&mode_args, "human");
+            if !has_flag(&mode_args, "files") {
+                print!("{}", render_du(&fs, &largest(&fs, true, top), human));
+            }
+            if !has_flag(&mode_args, "dirs") {
+                print!("{}", render_du(&fs, &largest(&fs, false, top), human));
+            }
+            return;
+        }
+        Some("find") => {
+            print_found(&fs, &mode_args);
+            return;
+        }
+        Some("materialize") => {
+            let Some(target) = mode_args.first() else {
+                eprintln!("Usage: materialize <target directory>");
+                std::process::exit(1);
+            };
+            if let Err(error) = materialize(&fs, Path::new(target)) {
+                eprintln!("Could not materialize into {}: {}", target, error);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("plan") => {
+            print_plan(&fs, &mode_args);
+            return;
+        }
+        Some(mode) => {
+            eprintln!(
+                "Unknown mode '{}' (available: tree, du, find, plan, check, generate, scan, materialize)",
+                mode
+            );
+            std::process::exit(1);
+        }
+    }
+    let part_one = solve_part_one(&fs, 100000);
+    let part_two = solve_part_two(&fs, 70000000, 30000000, verbose);
 
     println!("Part one: {:?}", part_one);
     println!("Part two: {:?}", part_two);
 }
 
-fn solve_part_one(dirs_sizes: &HashMap<String, usize>, threshold: usize) -> usize {
-    dirs_sizes
+/// `tree [<path>] --depth <n> --sort name|size --human`
+fn print_tree(fs: &FileSystem, mode_args: &[String]) {
+    let path = match mode_args.first() {
+        Some(arg) if !arg.starts_with("--") => arg.as_str(),
+        _ => "/",
+    };
+    let Some(from) = fs.lookup(path) else {
+        eprintln!("No such file or directory: {}", path);
+        std::process::exit(1);
+    };
+    let depth = flag_value(mode_args, "depth")
+        .map(|value| value.parse::<usize>().expect("Invalid --depth!"));
+    let sort = match flag_value(mode_args, "sort") {
+        Some(name) => SortBy::parse(name).expect("Sort by name or size"),
+        None => SortBy::Name,
+    };
+    print!(
+        "{}",
+        render_tree(fs, from, depth, sort, has_flag(mode_args, "human"))
+    );
+}
+
+/// `find [<glob>] --size <>100k,<=2M...> --type f|d --human`
+fn print_found(fs: &FileSystem, mode_args: &[String]) {
+    let mut query = Query::default();
+    if let Some(pattern) = mode_args.first().filter(|arg| !arg.starts_with("--")) {
+        query = query.with_pattern(pattern).unwrap_or_else(|| {
+            eprintln!("Invalid pattern: {}", pattern);
+            std::process::exit(1);
+        });
+    }
+    if let Some(sizes) = flag_value(mode_args, "size") {
+        query.sizes = sizes
+            .split(',')
+            .map(|size| SizePredicate::parse(size).expect("Sizes look like >100k or <=2M"))
+            .collect();
+    }
+    if let Some(name) = flag_value(mode_args, "type") {
+        query.node_type = Some(NodeType::parse(name).expect("Type is f or d"));
+    }
+    print!(
+        "{}",
+        render_du(fs, &find(fs, &query), has_flag(mode_args, "human"))
+    );
+}
+
+/// `plan --objective bytes|dirs --exclude <path,path...> --disk <size> --patch <size>`
+fn print_plan(fs: &FileSystem, mode_args: &[String]) {
+    let objective = match flag_value(mode_args, "objective") {
+        Some(name) => Objective::parse(name).expect("Objective is bytes or dirs"),
+        None => Objective::MinBytes,
+    };
+    let excluded = flag_value(mode_args, "exclude")
+        .map(|paths| paths.split(',').collect::<Vec<&str>>())
+        .unwrap_or_default()
+        .iter()
+        .map(|path| match fs.lookup(path) {
+            Some(id) if fs.node(id).is_dir() => id,
+            _ => {
+                eprintln!("No such directory: {}", path);
+                std::process::exit(1);
+            }
+        })
+        .collect::<Vec<NodeId>>();
+    let disk_size = parse_count_flag(mode_args, "disk", 70000000);
+    let update_patch_size = parse_count_flag(mode_args, "patch", 30000000);
+
+    let used = fs.sizes()[ROOT];
+    let required = (update_patch_size + used).saturating_sub(disk_size);
+    println!(
+        "used: {} / unused: {} / required: {}",
+        used,
+        disk_size.saturating_sub(used),
+        required
+    );
+    let plan = match plan_deletion(fs, required, objective, &excluded) {
+        Ok(plan) => plan,
+        Err(error) => {
+            eprintln!("Can not free {} bytes: {}", required, error);
+            std::process::exit(1);
+        }
+    };
+    for &dir in &plan.dirs {
+        println!("rm -r {} # {}", fs.path(dir), fs.sizes()[dir]);
+    }
+    println!(
+        "Deleting {} directories frees {} bytes, leaving {} unused",
+        plan.dirs.len(),
+        plan.freed,
+        disk_size.saturating_sub(used) + plan.freed
+    );
+}
+
+/// `check --strict`, exits with 1 if there are errors
+fn check_transcript(input_file: &str, mode_args: &[String]) {
+    let mode = match has_flag(mode_args, "strict") {
+        true => Mode::Strict,
+        false => Mode::Lenient,
+    };
+    let validation = validate_file(input_file);
+    for problem in &validation.problems {
+        let level = if problem.is_error(mode) {
+            "error"
+        } else {
+            "warning"
+        };
+        println!("{}: {}", level, problem);
+    }
+    let errors = validation.errors(mode);
+    println!(
+        "{} errors, {} warnings",
+        errors,
+        validation.problems.len() - errors
+    );
+    if errors > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// `<directory> scan --order dfs|bfs|revisit --seed <n>`, the transcript of a real directory
+fn print_scan(directory: &str, mode_args: &[String]) {
+    let (fs, skipped) = scan(Path::new(directory)).unwrap_or_else(|error| {
+        eprintln!("Could not scan {}: {}", directory, error);
+        std::process::exit(1);
+    });
+    for path in skipped {
+        eprintln!("Skipped {}", path.display());
+    }
+    print_walk(
+        &fs,
+        mode_args,
+        &mut Rng::new(parse_count_flag(mode_args, "seed", 7) as u64),
+    );
+}
+
+/// `generate --from transcript|description|random --order dfs|bfs|revisit --seed <n>`
+/// with `--dirs <n> --files <n> --max-size <n>` for random trees, which ignore the input file
+fn print_transcript(input_file: &str, mode_args: &[String]) {
+    let mut rng = Rng::new(parse_count_flag(mode_args, "seed", 7) as u64);
+    let fs = match flag_value(mode_args, "from").unwrap_or("transcript") {
+        "transcript" => build_tree(input_file, false),
+        "description" => tree_from_description(yield_lines_trimmed(input_file)),
+        "random" => random_tree(
+            &mut rng,
+            parse_count_flag(mode_args, "dirs", 20),
+            parse_count_flag(mode_args, "files", 40),
+            parse_count_flag(mode_args, "max-size", 300000),
+        ),
+        from => {
+            eprintln!("Unknown tree source '{}'", from);
+            std::process::exit(1);
+        }
+    };
+    print_walk(&fs, mode_args, &mut rng);
+}
+
+fn print_walk(fs: &FileSystem, mode_args: &[String], rng: &mut Rng) {
+    let order = match flag_value(mode_args, "order") {
+        Some(name) => WalkOrder::parse(name).expect("Walk order is dfs, bfs or revisit"),
+        None => WalkOrder::DepthFirst,
+    };
+    for line in transcript(fs, order, rng) {
+        println!("{}", line);
+    }
+}
+
+fn parse_count_flag(mode_args: &[String], name: &str, default: usize) -> usize {
+    return match flag_value(mode_args, name) {
+        Some(value) => value
+            .parse::<usize>()
+            .unwrap_or_else(|_| panic!("Invalid --{}!", name)),
+        None => default,
+    };
+}
+
+fn solve_part_one(fs: &FileSystem, threshold: usize) -> usize {
+    fs.dir_sizes()
         .iter()
         .filter_map(
-            |(_, &value)| {
+            |&(_, value)| {
                 if value < threshold {
                     Some(value)
                 } else {
@@ -28,12 +267,13 @@ fn solve_part_one(dirs_sizes: &HashMap<String, usize>, threshold: usize) -> usiz
 }
 
 fn solve_part_two(
-    dirs_sizes: &HashMap<String, usize>,
+    fs: &FileSystem,
     disk_size: usize,
     update_patch_size: usize,
     verbose: bool,
 ) -> usize {
-    let total_used = dirs_sizes.get("/").expect("Dirs must contain root dir.");
+    let dirs_sizes = fs.dir_sizes();
+    let total_used = fs.sizes()[ROOT];
 
     let unused_space = disk_size - total_used;
     let required_space = update_patch_size - unused_space;
@@ -47,7 +287,7 @@ fn solve_part_two(
 
     return dirs_sizes
         .iter()
-        .filter_map(|(_, &value)| {
+        .filter_map(|&(_, value)| {
             if value >= required_space {
                 Some(value)
             } else {
@@ -58,102 +298,60 @@ fn solve_part_two(
         .expect("Could not find directory bellow the threshold");
 }
 
-fn calc_dirs_sizes(input_file: &str, verbose: bool) -> HashMap<String, usize> {
-    let mut pwd = Vec::<String>::new();
-    let mut size_map = HashMap::<String, usize>::new();
-
-    for line in yield_lines_trimmed(input_file) {
-        let cmd = parse(&line);
-        if verbose {
-            println!("{:?}", cmd);
-        }
-        cmd.apply(&mut pwd, &mut size_map);
-        if verbose {
-            println!("pwd {:?}", pwd);
-            println!("sizes {:?}", size_map);
-        }
-    }
-    return size_map;
-}
-
-fn parse(line: &str) -> Cmd {
-    let split = line.split(' ').collect::<Vec<&str>>();
-
-    match (split[0], split[1]) {
-        ("$", "ls") => Cmd::Ls,
-        ("dir", _) => Cmd::LsDirListed,
-        ("$", "cd") => Cmd::Cd {
-            path: String::from(split[2]),
-        },
-        _ => Cmd::LsFileListed {
-            size: split[0]
-                .parse::<usize>()
-                .expect(format!("Could not read file size from {}", split[0]).as_str()),
-        },
-    }
-}
-
-#[derive(Debug)]
-enum Cmd {
-    Cd { path: String },
-    Ls,
-    LsDirListed,
-    LsFileListed { size: usize },
-}
-
-impl Cmd {
-    fn apply(&self, pwd: &mut Vec<String>, size_map: &mut HashMap<String, usize>) {
-        match &self {
-            Cmd::Cd { path } => match path.as_str() {
-                "/" => {
-                    pwd.clear();
-                    pwd.push(path.clone());
-                }
-                ".." => {
-                    pwd.pop();
-                }
-                _ => {
-                    pwd.push(path.clone());
-                }
-            },
-            Cmd::LsFileListed { size } => {
-                for dir in every_sub_dir(&pwd) {
-                    let curr_dir_size = match size_map.get(&dir) {
-                        Some(value) => value + size,
-                        None => *size,
-                    };
-                    size_map.insert(dir, curr_dir_size);
-                }
-            }
-            Cmd::Ls => { /* the actual data comes from LsFileListed */ }
-            Cmd::LsDirListed => { /* the actual data comes from LsFileListed */ }
-        };
-    }
-}
-
-pub fn every_sub_dir(pwd: &Vec<String>) -> Vec<String> {
-    let mut dirs = Vec::new();
-    for i in 0..pwd.len() {
-        dirs.push(pwd[0..=i].join("/"));
-    }
-    return dirs;
-}
-
 #[cfg(test)]
 mod tests {
     use crate::*;
 
     #[test]
     fn test_part_one() {
-        let dirs_sizes = calc_dirs_sizes("./data_input.txt", false);
-        let part_one = solve_part_one(&dirs_sizes, 100000);
+        let fs = build_tree("./data_input.txt", false);
+        let part_one = solve_part_one(&fs, 100000);
         assert_eq!(part_one, 1844187);
     }
 
     #[test]
     fn test_part_two() {
-        let dirs_sizes = calc_dirs_sizes("./data_input.txt", false);
-        let part_two = solve_part_two(&dirs_sizes, 70000000, 30000000, false);
+        let fs = build_tree("./data_input.txt", false);
+        let part_two = solve_part_two(&fs, 70000000, 30000000, false);
         assert_eq!(part_two, 4978279);
     }
+
+    #[test]
+    fn test_generated_transcripts_round_trip() {
+        let mut rng = Rng::new(47);
+        for _ in 0..50 {
+            let dirs = rng.between(0, 30);
+            let files = rng.between(1, 60);
+            let fs = random_tree(&mut rng, dirs, files, 200000);
+            let used = fs.sizes()[ROOT];
+            let threshold = rng.between(1, used);
+            // so that at least the root frees enough
+            let (disk_size, patch_size) = (2 * used, used + rng.between(1, used));
+            let expected = (
+                solve_part_one(&fs, threshold),
+                solve_part_two(&fs, disk_size, patch_size, false),
+            );
+
+            for order in WalkOrder::ALL {
+                let lines = transcript(&fs, order, &mut rng);
+                let rebuilt = day_07::build_tree_from(lines.into_iter(), false);
+                let found = (
+                    solve_part_one(&rebuilt, threshold),
+                    solve_part_two(&rebuilt, disk_size, patch_size, false),
+                );
+                assert_eq!(found, expected, "{:?}", order);
+
+                let entries = |fs: &FileSystem| {
+                    let sizes = fs.sizes();
+                    let mut entries = fs
+                        .ids()
+                        .map(|id| (fs.path(id), sizes[id]))
+                        .collect::<Vec<_>>();
+                    entries.sort();
+                    return entries;
+                };
+                assert_eq!(entries(&rebuilt), entries(&fs));
+            }
+        }
+    }
 }
@@ -0,0 +1,349 @@
+use std::fmt;
+
+use crate::fs::{FileSystem, Node, NodeId, ROOT};
+
+/// Cells of the table finding the plan back, 4 bytes each.
+const MAX_TABLE_CELLS: usize = 1 << 26;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    /// delete as few bytes as possible
+    MinBytes,
+    /// delete as few directories as possible, then as few bytes
+    FewestDirs,
+}
+
+impl Objective {
+    pub fn parse(name: &str) -> Option<Self> {
+        return match name {
+            "bytes" => Some(Objective::MinBytes),
+            "dirs" => Some(Objective::FewestDirs),
+            _ => None,
+        };
+    }
+}
+
+/// Directories to delete, none of them inside another one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub dirs: Vec<NodeId>,
+    pub freed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanError {
+    /// not enough bytes outside the excluded directories
+    Impossible,
+    /// the search would need a table of more than `MAX_TABLE_CELLS` cells
+    TooLarge { cells: usize },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            PlanError::Impossible => write!(f, "not enough bytes outside the excluded directories"),
+            PlanError::TooLarge { cells } => write!(
+                f,
+                "the search would need {} table cells, more than the {} allowed",
+                cells, MAX_TABLE_CELLS
+            ),
+        };
+    }
+}
+
+/// Set of sums below a limit, as bits.
+#[derive(Clone)]
+struct Sums {
+    words: Vec<u64>,
+    limit: usize,
+}
+
+impl Sums {
+    fn new(limit: usize) -> Self {
+        return Self {
+            words: vec![0; limit.div_ceil(64)],
+            limit,
+        };
+    }
+
+    fn insert(&mut self, sum: usize) {
+        self.words[sum / 64] |= 1 << (sum % 64);
+    }
+
+    /// Smallest sum of the set that is at least `min`.
+    fn first_from(&self, min: usize) -> Option<usize> {
+        let mut w = min / 64;
+        let mut word = *self.words.get(w)? & (u64::MAX << (min % 64));
+        while word == 0 {
+            w += 1;
+            word = *self.words.get(w)?;
+        }
+        return Some(w * 64 + word.trailing_zeros() as usize);
+    }
+
+    /// Adds `shift` to every sum of `self` and puts the results below the limit in `target`,
+    /// calling `on_new` with the ones that were not there yet.
+    fn shift_into(&self, shift: usize, target: &mut Sums, mut on_new: impl FnMut(usize)) {
+        let (word_shift, bit_shift) = (shift / 64, shift % 64);
+        for w in word_shift..target.words.len() {
+            let mut shifted = self.words[w - word_shift] << bit_shift;
+            if bit_shift > 0 && w > word_shift {
+                shifted |= self.words[w - word_shift - 1] >> (64 - bit_shift);
+            }
+            if w + 1 == target.words.len() && !target.limit.is_multiple_of(64) {
+                shifted &= (1 << (target.limit % 64)) - 1;
+            }
+            let mut new = shifted & !target.words[w];
+            target.words[w] |= new;
+            while new != 0 {
+                on_new(w * 64 + new.trailing_zeros() as usize);
+                new &= new - 1;
+            }
+        }
+    }
+}
+
+/// Best set of directories to delete to free `required` bytes.
+/// Directories in `excluded` are never deleted, neither is anything inside them nor any
+/// directory containing them.
+///
+/// Picking directories that free at least some amount is a subset sum, solved by going
+/// through the directories in depth first order with the sums reachable so far (below
+/// `required`, the rest are already enough). Deleting a directory skips everything inside
+/// of it, so its sums only join the others once its last subdirectory is passed, and since
+/// not deleting anything keeps every sum the set only ever grows. The directory that first
+/// reached a sum is then all it takes to find the plan back. `MinBytes` only needs the one
+/// set, `FewestDirs` has one per number of directories and tries with more and more of them.
+/// Sums are counted in units of the largest size all directories are a multiple of, the
+/// table going from the last reached sum to the directory reaching it would otherwise take
+/// 4 bytes per byte to free; past `MAX_TABLE_CELLS` the search is refused.
+pub fn plan_deletion(
+    fs: &FileSystem,
+    required: usize,
+    objective: Objective,
+    excluded: &[NodeId],
+) -> Result<Plan, PlanError> {
+    if required == 0 {
+        return Ok(Plan {
+            dirs: Vec::new(),
+            freed: 0,
+        });
+    }
+    let search = Search::new(fs, required, excluded);
+    return match objective {
+        Objective::MinBytes => search.run(None),
+        Objective::FewestDirs => {
+            // nothing to try if even the fewest bytes can't be freed
+            let plan = search.run(None)?;
+            for max_dirs in 1..plan.dirs.len() {
+                match search.run(Some(max_dirs)) {
+                    Err(PlanError::Impossible) => continue,
+                    found => return found,
+                }
+            }
+            Ok(plan)
+        }
+    };
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    return if b == 0 { a } else { gcd(b, a % b) };
+}
+
+struct Search {
+    /// bytes every size is counted in
+    unit: usize,
+    /// in units, rounded up
+    required: usize,
+    /// directories that can be deleted or contain some, depth first
+    order: Vec<NodeId>,
+    /// position in `order` right after everything inside the directory at each position
+    ends: Vec<usize>,
+    /// in units for the directories in `order` that can be deleted
+    sizes: Vec<usize>,
+    /// directories containing excluded ones
+    protected: Vec<bool>,
+}
+
+impl Search {
+    fn new(fs: &FileSystem, required: usize, excluded: &[NodeId]) -> Self {
+        let mut protected = vec![false; fs.len()];
+        for &id in excluded {
+            let mut current = fs.parent(id);
+            while let Some(dir) = current {
+                protected[dir] = true;
+                current = fs.parent(dir);
+            }
+        }
+
+        let mut search = Self {
+            unit: 1,
+            required,
+            order: Vec::new(),
+            ends: Vec::new(),
+            sizes: fs.sizes(),
+            protected,
+        };
+        search.visit(fs, ROOT, excluded);
+
+        let deletable = search
+            .order
+            .iter()
+            .copied()
+            .filter(|&id| !search.protected[id])
+            .collect::<Vec<NodeId>>();
+        search.unit = deletable
+            .iter()
+            .fold(0, |unit, &id| gcd(unit, search.sizes[id]))
+            .max(1);
+        for id in deletable {
+            search.sizes[id] /= search.unit;
+        }
+        search.required = required.div_ceil(search.unit);
+        return search;
+    }
+
+    fn visit(&mut self, fs: &FileSystem, id: NodeId, excluded: &[NodeId]) {
+        if excluded.contains(&id) {
+            return;
+        }
+        let Node::Dir(dir) = fs.node(id) else {
+            return;
+        };
+        let position = self.order.len();
+        self.order.push(id);
+        self.ends.push(0);
+        for &child in dir.children.values() {
+            self.visit(fs, child, excluded);
+        }
+        self.ends[position] = self.order.len();
+    }
+
+    /// Plan freeing the fewest bytes, with at most `max_dirs` directories if given.
+    fn run(&self, max_dirs: Option<usize>) -> Result<Plan, PlanError> {
+        // sets of the sums below `required`, one per number of directories deleted
+        // to get them when counting
+        let layers = max_dirs.unwrap_or(1);
+        let cells = layers.saturating_mul(self.required);
+        if cells > MAX_TABLE_CELLS {
+            return Err(PlanError::TooLarge { cells });
+        }
+        let mut reached = vec![Sums::new(self.required); layers];
+        reached[0].insert(0);
+        // position of the directory deleted last to first reach each sum
+        let mut last = vec![vec![u32::MAX; self.required]; layers];
+        // (end, layer, sums, position) waiting for the end of a deleted directory, the
+        // ends get smaller towards the top of the stack as the directories are nested
+        let mut pending: Vec<(usize, usize, Sums, usize)> = Vec::new();
+        // (freed, layer, sum before, position)
+        let mut best: Option<(usize, usize, usize, usize)> = None;
+
+        for (position, &id) in self.order.iter().enumerate() {
+            while pending.last().is_some_and(|&(end, ..)| end == position) {
+                let (_, layer, sums, taken) = pending.pop().unwrap();
+                let size = self.sizes[self.order[taken]];
+                let last = &mut last[layer];
+                sums.shift_into(size, &mut reached[layer], |sum| last[sum] = taken as u32);
+            }
+            if self.protected[id] {
+                continue;
+            }
+            let size = self.sizes[id];
+            for (layer, sums) in reached.iter().enumerate() {
+                let min = self.required.saturating_sub(size);
+                if let Some(sum) = sums.first_from(min) {
+                    if best.is_none_or(|(freed, ..)| sum + size < freed) {
+                        best = Some((sum + size, layer, sum, position));
+                    }
+                }
+                let next = if max_dirs.is_some() { layer + 1 } else { layer };
+                if size < self.required && next < layers {
+                    pending.push((self.ends[position], next, sums.clone(), position));
+                }
+            }
+        }
+
+        let Some((freed, mut layer, mut sum, position)) = best else {
+            return Err(PlanError::Impossible);
+        };
+        let mut dirs = vec![self.order[position]];
+        while sum != 0 {
+            let taken = last[layer][sum] as usize;
+            dirs.push(self.order[taken]);
+            sum -= self.sizes[self.order[taken]];
+            if max_dirs.is_some() {
+                layer -= 1;
+            }
+        }
+        dirs.reverse();
+        return Ok(Plan {
+            dirs,
+            freed: freed * self.unit,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build_tree;
+    use crate::plan::*;
+
+    #[test]
+    fn test_plan_deletion() {
+        let fs = build_tree("./data_sample.txt", false);
+        let (a, d) = (fs.lookup("/a").unwrap(), fs.lookup("/d").unwrap());
+        let e = fs.lookup("/a/e").unwrap();
+        let needed = 8381165;
+
+        let single = plan_deletion(&fs, needed, Objective::FewestDirs, &[]).unwrap();
+        assert_eq!(single.dirs, vec![d]);
+        assert_eq!(single.freed, 24933642);
+        assert_eq!(
+            plan_deletion(&fs, needed, Objective::MinBytes, &[]),
+            Ok(single)
+        );
+
+        // /a and /a/e together would double count /a/e
+        let plan = plan_deletion(&fs, 94853, Objective::MinBytes, &[]).unwrap();
+        assert_eq!((plan.dirs, plan.freed), (vec![a], 94853));
+        let plan = plan_deletion(&fs, 94853, Objective::MinBytes, &[e]).unwrap();
+        assert_eq!((plan.dirs.len(), plan.freed), (1, 24933642));
+
+        assert_eq!(
+            plan_deletion(&fs, needed, Objective::MinBytes, &[d]),
+            Err(PlanError::Impossible)
+        );
+        let plan = plan_deletion(&fs, 100, Objective::MinBytes, &[]).unwrap();
+        assert_eq!((plan.dirs, plan.freed), (vec![e], 584));
+
+        let mut fs = FileSystem::new();
+        for (name, size) in [("x", 60), ("y", 50), ("z", 45)] {
+            let dir = fs.add_dir(ROOT, name);
+            fs.add_file(dir, "file", size);
+        }
+        let plan = plan_deletion(&fs, 100, Objective::MinBytes, &[]).unwrap();
+        assert_eq!(plan.freed, 105);
+        assert_eq!(
+            plan.dirs.iter().map(|&id| fs.path(id)).collect::<Vec<_>>(),
+            ["/x", "/z"]
+        );
+        let plan = plan_deletion(&fs, 100, Objective::FewestDirs, &[]).unwrap();
+        assert_eq!((plan.dirs, plan.freed), (vec![ROOT], 155));
+
+        // terabytes only fit the table counted in units of the common divisor
+        let mut fs = FileSystem::new();
+        for (name, size) in [("x", 6_000_000_000_000), ("y", 4_000_000_000_000)] {
+            let dir = fs.add_dir(ROOT, name);
+            fs.add_file(dir, "file", size);
+        }
+        let z = fs.add_dir(ROOT, "z");
+        fs.add_file(z, "file", 3_000_000_000_000);
+        let plan = plan_deletion(&fs, 6_500_000_000_000, Objective::MinBytes, &[]).unwrap();
+        assert_eq!(plan.freed, 7_000_000_000_000);
+        fs.add_file(z, "odd", 1);
+        assert!(matches!(
+            plan_deletion(&fs, 6_500_000_000_000, Objective::MinBytes, &[]),
+            Err(PlanError::TooLarge { .. })
+        ));
+    }
+}
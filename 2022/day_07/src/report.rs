@@ -0,0 +1,163 @@
+use std::cmp::Reverse;
+
+use crate::fs::{FileSystem, Node, NodeId};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    Name,
+    /// largest first
+    Size,
+}
+
+impl SortBy {
+    pub fn parse(name: &str) -> Option<Self> {
+        return match name {
+            "name" => Some(SortBy::Name),
+            "size" => Some(SortBy::Size),
+            _ => None,
+        };
+    }
+}
+
+/// Sizes in bytes, or with binary units like `du -h` (`584`, `9.5K`, `93K`, `23M`).
+pub fn format_size(size: usize, human: bool) -> String {
+    if !human {
+        return size.to_string();
+    }
+    let units = ["K", "M", "G", "T", "P"];
+    let mut value = size as f64;
+    let mut unit = None;
+    for next_unit in units {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = Some(next_unit);
+    }
+    return match unit {
+        None => size.to_string(),
+        Some(unit) if value < 10.0 => format!("{:.1}{}", value, unit),
+        Some(unit) => format!("{:.0}{}", value, unit),
+    };
+}
+
+/// `tree` like drawing of `from` and everything below it, `max_depth` levels deep at most.
+pub fn render_tree(
+    fs: &FileSystem,
+    from: NodeId,
+    max_depth: Option<usize>,
+    sort: SortBy,
+    human: bool,
+) -> String {
+    let sizes = fs.sizes();
+    let mut out = format!("{} ({})\n", fs.path(from), format_size(sizes[from], human));
+    render_children(fs, &sizes, from, "", 1, max_depth, sort, human, &mut out);
+    return out;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_children(
+    fs: &FileSystem,
+    sizes: &[usize],
+    dir: NodeId,
+    prefix: &str,
+    depth: usize,
+    max_depth: Option<usize>,
+    sort: SortBy,
+    human: bool,
+    out: &mut String,
+) {
+    if max_depth.is_some_and(|max_depth| depth > max_depth) {
+        return;
+    }
+    let Node::Dir(dir) = fs.node(dir) else {
+        return;
+    };
+    let mut children = dir.children.values().copied().collect::<Vec<NodeId>>();
+    if sort == SortBy::Size {
+        // stable, so equal sizes stay sorted by name
+        children.sort_by_key(|&id| Reverse(sizes[id]));
+    }
+
+    for (i, &child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let node = fs.node(child);
+        let name = match node.is_dir() {
+            true => format!("{}/", node.name()),
+            false => node.name().to_string(),
+        };
+        out.push_str(&format!(
+            "{}{} {} ({})\n",
+            prefix,
+            if last { "└──" } else { "├──" },
+            name,
+            format_size(sizes[child], human)
+        ));
+        let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        render_children(
+            fs,
+            sizes,
+            child,
+            &prefix,
+            depth + 1,
+            max_depth,
+            sort,
+            human,
+            out,
+        );
+    }
+}
+
+/// `(node, size)` of the `top` largest directories, or files, largest first.
+pub fn largest(fs: &FileSystem, dirs: bool, top: usize) -> Vec<(NodeId, usize)> {
+    let sizes = fs.sizes();
+    let mut nodes = fs
+        .ids()
+        .filter(|&id| fs.node(id).is_dir() == dirs)
+        .map(|id| (id, sizes[id]))
+        .collect::<Vec<_>>();
+    nodes.sort_by_key(|&(id, size)| (Reverse(size), fs.path(id)));
+    nodes.truncate(top);
+    return nodes;
+}
+
+/// `du` like lines, the size then the path.
+pub fn render_du(fs: &FileSystem, nodes: &[(NodeId, usize)], human: bool) -> String {
+    return nodes
+        .iter()
+        .map(|&(id, size)| format!("{:<10}{}\n", format_size(size, human), fs.path(id)))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build_tree;
+    use crate::report::*;
+
+    #[test]
+    fn test_reports() {
+        assert_eq!(format_size(584, true), "584");
+        assert_eq!(format_size(9728, true), "9.5K");
+        assert_eq!(format_size(94853, true), "93K");
+        assert_eq!(format_size(24933642, true), "24M");
+
+        let fs = build_tree("./data_sample.txt", false);
+        let tree = render_tree(&fs, 0, Some(1), SortBy::Size, false);
+        assert_eq!(
+            tree,
+            "/ (48381165)\n\
+             ├── d/ (24933642)\n\
+             ├── b.txt (14848514)\n\
+             ├── c.dat (8504156)\n\
+             └── a/ (94853)\n"
+        );
+
+        let tree = render_tree(&fs, fs.lookup("/a").unwrap(), None, SortBy::Name, false);
+        assert!(tree.contains("├── e/ (584)\n│   └── i (584)\n├── f (29116)\n"));
+
+        let dirs = largest(&fs, true, 2);
+        assert_eq!(render_du(&fs, &dirs, true), "46M       /\n24M       /d\n");
+        let files = largest(&fs, false, 1);
+        assert_eq!(render_du(&fs, &files, false), "14848514  /b.txt\n");
+    }
+}
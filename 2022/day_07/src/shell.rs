@@ -0,0 +1,178 @@
+use crate::fs::{FileSystem, NodeId, ROOT};
+
+/// Where the commands run and where listed entries go (the directory given to the last
+/// `ls`, the working directory by default).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shell {
+    pub pwd: NodeId,
+    pub listing: NodeId,
+}
+
+impl Shell {
+    /// There are no users on the device, `~` is `/`.
+    pub const HOME: NodeId = ROOT;
+
+    pub fn new() -> Self {
+        return Self {
+            pwd: ROOT,
+            listing: ROOT,
+        };
+    }
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathStart {
+    Root,
+    Home,
+    Pwd,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathPart {
+    Parent,
+    Name(String),
+}
+
+/// Path given to `cd` or `ls`: `/x/y`, `~/x`, `a/b/../c`. `.` parts are dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellPath {
+    pub start: PathStart,
+    pub parts: Vec<PathPart>,
+}
+
+impl ShellPath {
+    pub fn parse(text: &str) -> Option<Self> {
+        if text.is_empty() {
+            return None;
+        }
+        let (start, rest) = if let Some(rest) = text.strip_prefix('/') {
+            (PathStart::Root, rest)
+        } else if text == "~" || text.starts_with("~/") {
+            (PathStart::Home, &text[1..])
+        } else {
+            (PathStart::Pwd, text)
+        };
+        let parts = rest
+            .split('/')
+            .filter(|name| !name.is_empty() && *name != ".")
+            .map(|name| match name {
+                ".." => PathPart::Parent,
+                name => PathPart::Name(String::from(name)),
+            })
+            .collect();
+        return Some(Self { start, parts });
+    }
+
+    pub fn start_dir(&self, pwd: NodeId) -> NodeId {
+        return match self.start {
+            PathStart::Root => ROOT,
+            PathStart::Home => Shell::HOME,
+            PathStart::Pwd => pwd,
+        };
+    }
+
+    /// Directory the path leads to from `pwd`, creating the missing ones like entering them
+    /// finds them. `..` at the root stays there.
+    pub fn resolve(&self, fs: &mut FileSystem, pwd: NodeId) -> NodeId {
+        let mut dir = self.start_dir(pwd);
+        for part in &self.parts {
+            dir = match part {
+                PathPart::Parent => fs.parent(dir).unwrap_or(ROOT),
+                PathPart::Name(name) => fs.add_dir(dir, name),
+            };
+        }
+        return dir;
+    }
+}
+
+/// Line of `ls -l`, e.g. `-rw-r--r-- 1 elf elves 14848514 Dec  1 09:00 b.txt`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LongEntry {
+    pub permissions: String,
+    pub links: usize,
+    pub owner: String,
+    pub group: String,
+    pub size: usize,
+    /// month, day and time or year, `Dec 1 09:00` or `Dec 1 2022`
+    pub modified: String,
+    pub name: String,
+}
+
+impl LongEntry {
+    pub fn parse(line: &str) -> Option<Self> {
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        if fields.len() < 9 {
+            return None;
+        }
+        let permissions = fields[0];
+        let kind = permissions.chars().next()?;
+        if permissions.chars().count() < 10 || !"-dlbcps".contains(kind) {
+            return None;
+        }
+        let name = fields[8..].join(" ");
+        return Some(Self {
+            permissions: String::from(permissions),
+            links: fields[1].parse().ok()?,
+            owner: String::from(fields[2]),
+            group: String::from(fields[3]),
+            size: fields[4].parse().ok()?,
+            modified: fields[5..8].join(" "),
+            // links show their target, `name -> target`
+            name: match name.split_once(" -> ") {
+                Some((name, _)) if kind == 'l' => String::from(name),
+                _ => name,
+            },
+        });
+    }
+
+    pub fn is_dir(&self) -> bool {
+        return self.permissions.starts_with('d');
+    }
+
+    pub fn is_file(&self) -> bool {
+        return self.permissions.starts_with('-');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shell::*;
+
+    #[test]
+    fn test_paths_and_long_entries() {
+        let path = ShellPath::parse("a/./b/../c").unwrap();
+        assert_eq!(path.start, PathStart::Pwd);
+        assert_eq!(
+            path.parts,
+            [
+                PathPart::Name(String::from("a")),
+                PathPart::Name(String::from("b")),
+                PathPart::Parent,
+                PathPart::Name(String::from("c")),
+            ]
+        );
+        let mut fs = FileSystem::new();
+        let x = fs.add_dir(ROOT, "x");
+        let c = path.resolve(&mut fs, x);
+        assert_eq!(fs.path(c), "/x/a/c");
+        assert!(fs.lookup("/x/a/b").is_some());
+        assert_eq!(ShellPath::parse("/../x/").unwrap().resolve(&mut fs, c), x);
+        assert_eq!(ShellPath::parse("~").unwrap().resolve(&mut fs, c), ROOT);
+        assert_eq!(ShellPath::parse("/").unwrap().parts, []);
+
+        let entry = LongEntry::parse("-rw-r--r-- 1 elf elves 14848514 Dec  1 09:00 b.txt").unwrap();
+        assert_eq!((entry.size, entry.name.as_str()), (14848514, "b.txt"));
+        assert_eq!(entry.modified, "Dec 1 09:00");
+        assert!(entry.is_file());
+        let entry = LongEntry::parse("lrwxrwxrwx 1 elf elves 5 Dec  1  2022 l -> b.txt").unwrap();
+        assert_eq!(entry.name, "l");
+        assert!(!entry.is_file() && !entry.is_dir());
+        assert_eq!(LongEntry::parse("dir a"), None);
+    }
+}
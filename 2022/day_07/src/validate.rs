@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::fs::{FileSystem, Node, NodeId, ROOT};
+use crate::shell::{LongEntry, PathPart, Shell, ShellPath};
+use crate::{try_parse, Cmd};
+use utils::io::yield_lines_trimmed;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// every problem is an error
+    Strict,
+    /// only the problems that make the sizes unreliable are errors, the others are warnings
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProblemKind {
+    UnknownCommand {
+        line: String,
+    },
+    CdIntoUnlisted {
+        path: String,
+    },
+    RepeatedLs {
+        path: String,
+        first_line: usize,
+    },
+    ConflictingSize {
+        path: String,
+        size: usize,
+        listed: usize,
+    },
+    CdAboveRoot,
+    PwdMismatch {
+        printed: String,
+        pwd: String,
+    },
+    /// a file listed or entered as a directory, or the other way around
+    TypeConflict {
+        path: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub line: usize,
+    pub kind: ProblemKind,
+}
+
+impl Problem {
+    pub fn is_error(&self, mode: Mode) -> bool {
+        return match self.kind {
+            ProblemKind::UnknownCommand { .. }
+            | ProblemKind::ConflictingSize { .. }
+            | ProblemKind::PwdMismatch { .. }
+            | ProblemKind::TypeConflict { .. } => true,
+            _ => mode == Mode::Strict,
+        };
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        return match &self.kind {
+            ProblemKind::UnknownCommand { line } => write!(f, "unknown command '{}'", line),
+            ProblemKind::CdIntoUnlisted { path } => {
+                write!(f, "cd into {} which was never listed", path)
+            }
+            ProblemKind::RepeatedLs { path, first_line } => {
+                write!(f, "{} already listed on line {}", path, first_line)
+            }
+            ProblemKind::ConflictingSize { path, size, listed } => write!(
+                f,
+                "{} listed with size {} but was {} before",
+                path, listed, size
+            ),
+            ProblemKind::CdAboveRoot => write!(f, "cd .. above /"),
+            ProblemKind::PwdMismatch { printed, pwd } => {
+                write!(
+                    f,
+                    "pwd printed {} but the working directory is {}",
+                    printed, pwd
+                )
+            }
+            ProblemKind::TypeConflict { path } => {
+                write!(f, "{} used both as a file and as a directory", path)
+            }
+        };
+    }
+}
+
+/// Tree rebuilt from a transcript along with what was wrong with it. Lines with unknown
+/// commands or mixing up files and directories are skipped, a file listed again keeps its
+/// first size and everything else is applied like `build_tree` does.
+pub struct Validation {
+    pub fs: FileSystem,
+    pub problems: Vec<Problem>,
+}
+
+impl Validation {
+    pub fn errors(&self, mode: Mode) -> usize {
+        return self.problems.iter().filter(|p| p.is_error(mode)).count();
+    }
+}
+
+pub fn validate(lines: impl Iterator<Item = String>) -> Validation {
+    let mut fs = FileSystem::new();
+    let mut shell = Shell::new();
+    let mut problems = Vec::new();
+    // directories seen in a listing, by the line of the listing
+    let mut listed_dirs = HashMap::from([(ROOT, 0)]);
+    let mut ls_lines: HashMap<NodeId, usize> = HashMap::new();
+    let mut after_pwd = false;
+
+    for (i, line) in lines.enumerate() {
+        let mut report = |kind| problems.push(Problem { line: i + 1, kind });
+        let Some(cmd) = try_parse(&line, after_pwd) else {
+            after_pwd = false;
+            report(ProblemKind::UnknownCommand { line });
+            continue;
+        };
+        after_pwd = matches!(cmd, Cmd::Pwd);
+        let path = match &cmd {
+            Cmd::Cd { path }
+            | Cmd::Ls {
+                path: Some(path), ..
+            } => Some(path),
+            _ => None,
+        };
+        if let Some(file) = path.and_then(|path| file_on_path(&fs, shell.pwd, path)) {
+            report(ProblemKind::TypeConflict {
+                path: fs.path(file),
+            });
+            continue;
+        }
+        let conflict = match &cmd {
+            Cmd::Cd { path } => {
+                let mut dir = path.start_dir(shell.pwd);
+                for part in &path.parts {
+                    match part {
+                        PathPart::Parent if dir == ROOT => report(ProblemKind::CdAboveRoot),
+                        PathPart::Parent => dir = fs.parent(dir).unwrap_or(ROOT),
+                        PathPart::Name(name) => match fs.child(dir, name) {
+                            Some(id) if listed_dirs.contains_key(&id) => dir = id,
+                            _ => {
+                                let parent = fs.path(dir);
+                                report(ProblemKind::CdIntoUnlisted {
+                                    path: format!("{}/{}", parent.trim_end_matches('/'), name),
+                                });
+                                break;
+                            }
+                        },
+                    }
+                }
+                None
+            }
+            Cmd::PwdOutput { path } => {
+                let pwd = fs.path(shell.pwd);
+                let printed = match path.trim_end_matches('/') {
+                    "" => "/",
+                    printed => printed,
+                };
+                (printed != pwd).then(|| ProblemKind::PwdMismatch {
+                    printed: path.clone(),
+                    pwd,
+                })
+            }
+            Cmd::LsDirListed { name } => conflicting_entry(&fs, shell.listing, name, None),
+            Cmd::LsFileListed { name, size } => {
+                conflicting_entry(&fs, shell.listing, name, Some(*size))
+            }
+            Cmd::LsLongListed { entry } if entry.is_dir() => {
+                conflicting_entry(&fs, shell.listing, &entry.name, None)
+            }
+            Cmd::LsLongListed { entry } if entry.is_file() => {
+                conflicting_entry(&fs, shell.listing, &entry.name, Some(entry.size))
+            }
+            _ => None,
+        };
+        if let Some(kind) = conflict {
+            report(kind);
+            continue;
+        }
+        cmd.apply(&mut fs, &mut shell);
+        match &cmd {
+            Cmd::Ls { .. } => {
+                if let Some(&first_line) = ls_lines.get(&shell.listing) {
+                    report(ProblemKind::RepeatedLs {
+                        path: fs.path(shell.listing),
+                        first_line,
+                    });
+                } else {
+                    ls_lines.insert(shell.listing, i + 1);
+                }
+            }
+            Cmd::LsDirListed { name }
+            | Cmd::LsLongListed {
+                entry: LongEntry { name, .. },
+            } => {
+                let id = fs.child(shell.listing, name);
+                if let Some(id) = id.filter(|&id| fs.node(id).is_dir()) {
+                    listed_dirs.entry(id).or_insert(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    return Validation { fs, problems };
+}
+
+/// The problem with listing `name` in `dir`, as a file of `size` or as a directory (`None`),
+/// if it was listed as something else before.
+fn conflicting_entry(
+    fs: &FileSystem,
+    dir: NodeId,
+    name: &str,
+    size: Option<usize>,
+) -> Option<ProblemKind> {
+    let id = fs.child(dir, name)?;
+    return match (fs.node(id), size) {
+        (Node::File(file), Some(size)) if file.size != size => Some(ProblemKind::ConflictingSize {
+            path: fs.path(id),
+            size: file.size,
+            listed: size,
+        }),
+        (Node::File(_), None) | (Node::Dir(_), Some(_)) => {
+            Some(ProblemKind::TypeConflict { path: fs.path(id) })
+        }
+        _ => None,
+    };
+}
+
+/// First file `path` would have to go through from `pwd`.
+fn file_on_path(fs: &FileSystem, pwd: NodeId, path: &ShellPath) -> Option<NodeId> {
+    let mut dir = path.start_dir(pwd);
+    for part in &path.parts {
+        dir = match part {
+            PathPart::Parent => fs.parent(dir).unwrap_or(ROOT),
+            PathPart::Name(name) => match fs.child(dir, name) {
+                Some(id) if !fs.node(id).is_dir() => return Some(id),
+                Some(id) => id,
+                // the rest gets created as directories
+                None => return None,
+            },
+        };
+    }
+    return None;
+}
+
+pub fn validate_file(input_file: &str) -> Validation {
+    return validate(yield_lines_trimmed(input_file));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validate::*;
+
+    #[test]
+    fn test_validate() {
+        let transcript = "\
+            $ cd /
+            $ ls
+            dir a
+            100 b.txt
+            $ cd a
+            $ ls
+            5 c
+            $ cd ..
+            $ ls
+            dir a
+            200 b.txt
+            $ cd x
+            $ cd ..
+            $ cd ..
+            $ cd ..
+            $ rm -rf a
+            lorem ipsum dolor
+            dir b.txt
+            100 a
+            $ cd b.txt
+            $ ls -l a/c
+            $ pwd
+            /a
+            /";
+        let validation = validate(transcript.lines().map(|line| line.trim().to_string()));
+        let lines = validation
+            .problems
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            lines,
+            [
+                "line 9: / already listed on line 2",
+                "line 11: /b.txt listed with size 200 but was 100 before",
+                "line 12: cd into /x which was never listed",
+                "line 14: cd .. above /",
+                "line 15: cd .. above /",
+                "line 16: unknown command '$ rm -rf a'",
+                "line 17: unknown command 'lorem ipsum dolor'",
+                "line 18: /b.txt used both as a file and as a directory",
+                "line 19: /a used both as a file and as a directory",
+                "line 20: /b.txt used both as a file and as a directory",
+                "line 21: /a/c used both as a file and as a directory",
+                "line 23: pwd printed /a but the working directory is /",
+                "line 24: unknown command '/'",
+            ]
+        );
+        assert_eq!(validation.errors(Mode::Strict), 13);
+        assert_eq!(validation.errors(Mode::Lenient), 9);
+        assert_eq!(validation.fs.sizes()[ROOT], 105);
+
+        let validation = validate_file("./data_input.txt");
+        assert_eq!(validation.problems, []);
+    }
+}
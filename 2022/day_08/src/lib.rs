@@ -1,6 +1,11 @@
+pub mod parallel;
+pub mod paths;
+pub mod view;
+
 use utils::io::yield_lines_trimmed;
+use utils::random::Rng;
 
-type ProbeDirection = u8;
+pub type ProbeDirection = u8;
 
 #[derive(Debug)]
 pub struct TreeGrid {
@@ -27,7 +32,7 @@ impl TreeGrid {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Tree {
     pub height: usize,
     pub visible_from: ProbeDirection,
@@ -65,6 +70,41 @@ pub fn parse_tree_grid_from_file(file: &str) -> TreeGrid {
     };
 }
 
+/// Random forest for stress testing, with the same heights for the same `seed`.
+pub fn generate_tree_grid(width: usize, height: usize, seed: u64) -> TreeGrid {
+    let mut rng = Rng::new(seed);
+    let grid = (0..width * height)
+        .map(|_| Tree {
+            height: rng.below(10),
+            visible_from: NOT_VISIBLE,
+            scenic_score: 0,
+        })
+        .collect::<Vec<Tree>>();
+
+    return TreeGrid {
+        width,
+        height,
+        grid,
+    };
+}
+
+/// Runs the four visibility passes and the scenic scoring, split across `threads` when
+/// there is more than one (verbose output is only available single-threaded).
+pub fn calc_visibility_and_scenic_scores(trees: &mut TreeGrid, threads: usize, verbose: bool) {
+    if threads > 1 {
+        parallel::calc_visibility_from_outside_parallel(trees, threads);
+        parallel::calc_scenic_score_parallel(trees, threads);
+        return;
+    }
+
+    calc_visibility_from_outside(trees, TOP_DOWN, verbose);
+    calc_visibility_from_outside(trees, LEFT_RIGHT, verbose);
+    calc_visibility_from_outside(trees, BOTTOM_UP, verbose);
+    calc_visibility_from_outside(trees, RIGHT_LEFT, verbose);
+
+    calc_scenic_score(trees, verbose);
+}
+
 pub fn calc_visibility_from_outside(
     trees: &mut TreeGrid,
     direction: ProbeDirection,
@@ -172,40 +212,23 @@ fn get_scenic_core(
     direction: ProbeDirection,
     verbose: bool,
 ) -> usize {
-    let mut scenic_score = 0;
-    let (row_inc, col_inc): (i32, i32) = match direction {
-        BOTTOM_UP => (-1, 0),
-        TOP_DOWN => (1, 0),
-        LEFT_RIGHT => (0, 1),
-        RIGHT_LEFT => (0, -1),
-        _ => panic!("Invalid direction: {}", direction),
-    };
-
-    let from_tree = trees.get(row, col);
-    let mut i: i32 = row as i32;
-    let mut j: i32 = col as i32;
-    loop {
-        i += row_inc;
-        j += col_inc;
-
-        if out_of_grid_bounds(i, j, trees.width, trees.height) {
-            break;
-        }
-
-        let neighbor = trees.get(i as usize, j as usize);
-        scenic_score += 1;
-        if neighbor.height >= from_tree.height {
-            break; // view blocked
-        }
+    if !verbose {
+        return trees.viewing_distance(row, col, direction);
+    }
 
-        if verbose {
-            println!(
-                "Looking {} from ({}, {}) to ({}, {}) -> {}",
-                direction, row, col, i, j, scenic_score
-            )
-        }
+    let sight_line = trees.look_from(row, col, direction);
+    for (scenic_score, seen) in sight_line.seen.iter().enumerate() {
+        println!(
+            "Looking {} from ({}, {}) to ({}, {}) -> {}",
+            direction,
+            row,
+            col,
+            seen.row,
+            seen.col,
+            scenic_score + 1
+        )
     }
-    return scenic_score;
+    return sight_line.viewing_distance;
 }
 
 fn mark_if_visible_and_update_max_height(
@@ -219,7 +242,3 @@ fn mark_if_visible_and_update_max_height(
     }
     return max_height;
 }
-
-fn out_of_grid_bounds(row: i32, col: i32, grid_width: usize, grid_height: usize) -> bool {
-    return row < 0 || col < 0 || row >= grid_height as i32 || col >= grid_width as i32;
-}
@@ -1,8 +1,31 @@
+use std::io::stdin;
+use std::time::Instant;
+
+use day_08::paths::render_path;
+use day_08::view::{direction_name, render_view};
 use day_08::*;
+use utils::io::flag_value;
 
 fn main() {
-    let (input_file, verbose) = utils::io::parse_args();
-    let trees = parse_tree_grid(&input_file, verbose);
+    let (input_file, verbose, mode, mode_args) = utils::io::parse_args_with_mode();
+    let threads = parse_count_flag(&mode_args, "threads", 1);
+    if let Some(mode) = mode {
+        match mode.as_str() {
+            "view" => answer_view_queries(&input_file),
+            "path" => answer_path_queries(&input_file),
+            "bench" => {
+                let size = parse_count_flag(&mode_args, "size", 2000);
+                benchmark(size, threads);
+            }
+            _ => {
+                eprintln!("Unknown mode '{}' (available: view, path, bench)", mode);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let trees = parse_tree_grid(&input_file, threads, verbose);
 
     let part_one = solve_part_one(&trees);
     println!("Part one: {:?}", part_one);
@@ -11,15 +34,10 @@ fn main() {
     println!("Part two: {:?}", part_two);
 }
 
-fn parse_tree_grid(file: &str, verbose: bool) -> TreeGrid {
+fn parse_tree_grid(file: &str, threads: usize, verbose: bool) -> TreeGrid {
     let mut trees = parse_tree_grid_from_file(file);
 
-    calc_visibility_from_outside(&mut trees, TOP_DOWN, verbose);
-    calc_visibility_from_outside(&mut trees, LEFT_RIGHT, verbose);
-    calc_visibility_from_outside(&mut trees, BOTTOM_UP, verbose);
-    calc_visibility_from_outside(&mut trees, RIGHT_LEFT, verbose);
-
-    calc_scenic_score(&mut trees, verbose);
+    calc_visibility_and_scenic_scores(&mut trees, threads, verbose);
 
     if verbose {
         let visible: Vec<&Tree> = trees.grid.iter().filter(|t| t.visible_from > 0).collect();
@@ -30,6 +48,163 @@ fn parse_tree_grid(file: &str, verbose: bool) -> TreeGrid {
     return trees;
 }
 
+/// Reads `<row> <col>` pairs from stdin and prints what the tree on that coordinate can see.
+fn answer_view_queries(file: &str) {
+    let trees = parse_tree_grid_from_file(file);
+
+    for line in stdin().lines() {
+        let line = line.expect("Unexpected error reading line!");
+        let (row, col) = match parse_numbers(&line).as_deref() {
+            Some([]) => continue,
+            Some([row, col]) if *row < trees.height && *col < trees.width => (*row, *col),
+            _ => {
+                eprintln!(
+                    "Expected '<row> <col>' inside a {}x{} grid, got '{}'",
+                    trees.height, trees.width, line
+                );
+                continue;
+            }
+        };
+
+        let view = trees.view_from(row, col);
+        println!(
+            "Tree ({}, {}) with height {}, scenic score {}",
+            row,
+            col,
+            trees.get(row, col).height,
+            view.scenic_score()
+        );
+        for sight_line in view.sight_lines() {
+            let heights = sight_line
+                .seen
+                .iter()
+                .map(|t| t.height.to_string())
+                .collect::<Vec<String>>();
+            let blocked_by = match sight_line.blocked_by {
+                Some((row, col)) => format!("blocked by ({}, {})", row, col),
+                None => String::from("reaches the edge"),
+            };
+            println!(
+                "  {:<5} distance {} [{}] {}",
+                direction_name(sight_line.direction),
+                sight_line.viewing_distance,
+                heights.join(" "),
+                blocked_by
+            );
+        }
+        println!("{}", render_view(&trees, &view));
+    }
+}
+
+/// Reads `<row> <col> <row> <col> <max climb?>` from stdin and prints the shortest climbable path,
+/// the least effort path and the way out of the forest from the first tree.
+fn answer_path_queries(file: &str) {
+    let trees = parse_tree_grid_from_file(file);
+    let inside = |row: usize, col: usize| row < trees.height && col < trees.width;
+
+    for line in stdin().lines() {
+        let line = line.expect("Unexpected error reading line!");
+        let (from, to, max_climb) = match parse_numbers(&line).as_deref() {
+            Some([]) => continue,
+            Some([r1, c1, r2, c2]) if inside(*r1, *c1) && inside(*r2, *c2) => {
+                ((*r1, *c1), (*r2, *c2), 1)
+            }
+            Some([r1, c1, r2, c2, climb]) if inside(*r1, *c1) && inside(*r2, *c2) => {
+                ((*r1, *c1), (*r2, *c2), *climb)
+            }
+            _ => {
+                eprintln!(
+                    "Expected '<row> <col> <row> <col> <max climb?>' inside a {}x{} grid, got '{}'",
+                    trees.height, trees.width, line
+                );
+                continue;
+            }
+        };
+
+        match trees.climb_path(from, to, max_climb) {
+            Some(path) => {
+                println!(
+                    "Climbing at most {} from {:?} to {:?} takes {} steps",
+                    max_climb,
+                    from,
+                    to,
+                    path.len() - 1
+                );
+                println!("{}", render_path(&trees, &path));
+            }
+            None => println!(
+                "Cannot reach {:?} from {:?} climbing at most {}",
+                to, from, max_climb
+            ),
+        }
+
+        if let Some((_, effort)) = trees.least_effort_path(from, to) {
+            println!("Least effort from {:?} to {:?}: {}", from, to, effort);
+        }
+        match trees.way_out(from, max_climb) {
+            Some(path) => println!("Way out from {:?} takes {} steps", from, path.len() - 1),
+            None => println!("No way out from {:?} climbing at most {}", from, max_climb),
+        }
+        println!(
+            "Grove around {:?} has {} trees",
+            from,
+            trees.grove_from(from).len()
+        );
+        println!();
+    }
+}
+
+fn parse_count_flag(mode_args: &[String], name: &str, default: usize) -> usize {
+    return match flag_value(mode_args, name) {
+        Some(value) => match value.parse::<usize>() {
+            Ok(count) if count > 0 => count,
+            _ => {
+                eprintln!("Expected a positive number for --{}, got '{}'", name, value);
+                std::process::exit(1);
+            }
+        },
+        None => default,
+    };
+}
+
+/// Times the single-threaded and the multithreaded passes on a generated `size` x `size` grid
+/// (the input file is ignored) and checks both give the same trees.
+fn benchmark(size: usize, threads: usize) {
+    let threads = if threads > 1 {
+        threads
+    } else {
+        std::thread::available_parallelism().map_or(4, |n| n.get().max(2))
+    };
+
+    let mut serial = generate_tree_grid(size, size, 2022);
+    let start = Instant::now();
+    calc_visibility_and_scenic_scores(&mut serial, 1, false);
+    let serial_time = start.elapsed();
+
+    let mut parallel = generate_tree_grid(size, size, 2022);
+    let start = Instant::now();
+    calc_visibility_and_scenic_scores(&mut parallel, threads, false);
+    let parallel_time = start.elapsed();
+
+    assert_eq!(serial.grid, parallel.grid, "Multithreaded result differs!");
+    println!("Grid {}x{}", size, size);
+    println!("1 thread: {:?}", serial_time);
+    println!(
+        "{} threads: {:?} ({:.2}x)",
+        threads,
+        parallel_time,
+        serial_time.as_secs_f64() / parallel_time.as_secs_f64()
+    );
+}
+
+fn parse_numbers(line: &str) -> Option<Vec<usize>> {
+    return line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>().ok())
+        .collect();
+}
+
 fn solve_part_one(trees: &TreeGrid) -> usize {
     return trees.grid.iter().filter(|t| t.visible_from > 0).count();
 }
@@ -44,15 +219,22 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        let trees = parse_tree_grid("./data/input.txt", false);
+        let trees = parse_tree_grid("./data/input.txt", 1, false);
         let part_one = solve_part_one(&trees);
         assert_eq!(part_one, 1849);
     }
 
     #[test]
     fn test_part_two() {
-        let trees = parse_tree_grid("./data/input.txt", false);
+        let trees = parse_tree_grid("./data/input.txt", 1, false);
         let part_two = solve_part_two(&trees);
         assert_eq!(part_two, 201600);
     }
+
+    #[test]
+    fn test_multithreaded() {
+        let trees = parse_tree_grid("./data/input.txt", 4, false);
+        assert_eq!(solve_part_one(&trees), 1849);
+        assert_eq!(solve_part_two(&trees), 201600);
+    }
 }
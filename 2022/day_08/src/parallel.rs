@@ -0,0 +1,140 @@
+use std::thread;
+
+use crate::{
+    mark_if_visible_and_update_max_height, Tree, TreeGrid, BOTTOM_UP, LEFT_RIGHT, RIGHT_LEFT,
+    TOP_DOWN,
+};
+
+/// Same result as running `calc_visibility_from_outside` for the four directions, but
+/// rows and columns are split across `threads` scoped threads.
+pub fn calc_visibility_from_outside_parallel(trees: &mut TreeGrid, threads: usize) {
+    let width = trees.width;
+    let height = trees.height;
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    // rows are contiguous, so each thread owns a slice of them
+    let rows_per_thread = height.div_ceil(threads.max(1));
+    thread::scope(|scope| {
+        for rows in trees.grid.chunks_mut(rows_per_thread * width) {
+            scope.spawn(move || {
+                for row in rows.chunks_mut(width) {
+                    mark_line(row.iter_mut(), LEFT_RIGHT);
+                    mark_line(row.iter_mut().rev(), RIGHT_LEFT);
+                }
+            });
+        }
+    });
+
+    // columns are strided, so threads only read heights and the flags are merged afterwards
+    let cols_per_thread = width.div_ceil(threads.max(1));
+    let grid = &trees.grid;
+    let column_flags = thread::scope(|scope| {
+        let handles = (0..width)
+            .step_by(cols_per_thread)
+            .map(|first_col| {
+                let last_col = (first_col + cols_per_thread).min(width);
+                return scope.spawn(move || {
+                    return (first_col..last_col)
+                        .map(|col| column_visibility(grid, width, height, col))
+                        .collect::<Vec<Vec<u8>>>();
+                });
+            })
+            .collect::<Vec<_>>();
+
+        return handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Visibility thread panicked"))
+            .collect::<Vec<Vec<u8>>>();
+    });
+
+    for (col, flags) in column_flags.iter().enumerate() {
+        for (row, flag) in flags.iter().enumerate() {
+            trees.get_mut(row, col).visible_from |= flag;
+        }
+    }
+}
+
+/// Same result as `calc_scenic_score`, with rows split across `threads` scoped threads.
+pub fn calc_scenic_score_parallel(trees: &mut TreeGrid, threads: usize) {
+    let width = trees.width;
+    if width == 0 || trees.height == 0 {
+        return;
+    }
+
+    let rows_per_thread = trees.height.div_ceil(threads.max(1));
+    let mut scores = vec![0; trees.grid.len()];
+    let grid: &TreeGrid = trees;
+    thread::scope(|scope| {
+        for (chunk_idx, chunk) in scores.chunks_mut(rows_per_thread * width).enumerate() {
+            scope.spawn(move || {
+                let first_row = chunk_idx * rows_per_thread;
+                for (i, score) in chunk.iter_mut().enumerate() {
+                    let (row, col) = (first_row + i / width, i % width);
+                    *score = [BOTTOM_UP, TOP_DOWN, RIGHT_LEFT, LEFT_RIGHT]
+                        .iter()
+                        .map(|&direction| grid.viewing_distance(row, col, direction))
+                        .product();
+                }
+            });
+        }
+    });
+
+    for (tree, score) in trees.grid.iter_mut().zip(scores) {
+        tree.scenic_score = score;
+    }
+}
+
+fn mark_line<'a>(line: impl Iterator<Item = &'a mut Tree>, direction: u8) {
+    let mut max_height = None;
+    for tree in line {
+        max_height = mark_if_visible_and_update_max_height(tree, direction, max_height);
+    }
+}
+
+fn column_visibility(grid: &[Tree], width: usize, height: usize, col: usize) -> Vec<u8> {
+    let mut flags = vec![0; height];
+
+    let mut max_height = None;
+    for (row, flag) in flags.iter_mut().enumerate() {
+        let tree_height = grid[row * width + col].height;
+        if max_height.is_none_or(|max| max < tree_height) {
+            *flag |= TOP_DOWN;
+            max_height = Some(tree_height);
+        }
+    }
+
+    let mut max_height = None;
+    for (row, flag) in flags.iter_mut().enumerate().rev() {
+        let tree_height = grid[row * width + col].height;
+        if max_height.is_none_or(|max| max < tree_height) {
+            *flag |= BOTTOM_UP;
+            max_height = Some(tree_height);
+        }
+    }
+    return flags;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parallel::*;
+    use crate::*;
+
+    #[test]
+    fn test_parallel_matches_serial() {
+        for (width, height, threads) in [(37, 23, 4), (5, 64, 3), (64, 1, 8), (10, 10, 32)] {
+            let mut serial = generate_tree_grid(width, height, 42);
+            for direction in [TOP_DOWN, LEFT_RIGHT, BOTTOM_UP, RIGHT_LEFT] {
+                calc_visibility_from_outside(&mut serial, direction, false);
+            }
+            calc_scenic_score(&mut serial, false);
+
+            let mut parallel = generate_tree_grid(width, height, 42);
+            calc_visibility_from_outside_parallel(&mut parallel, threads);
+            calc_scenic_score_parallel(&mut parallel, threads);
+
+            assert_eq!(serial.grid, parallel.grid);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+
+use utils::pathfinding::{
+    bfs, dijkstra, grid_climb_path, grid_flood_fill, grid_neighbours, GridPos,
+};
+
+use crate::TreeGrid;
+
+impl TreeGrid {
+    /// Fewest steps from `from` to `to` climbing at most `max_climb` per step.
+    pub fn climb_path(&self, from: GridPos, to: GridPos, max_climb: usize) -> Option<Vec<GridPos>> {
+        return grid_climb_path(
+            self.width,
+            self.height,
+            |(row, col)| self.get(row, col).height,
+            from,
+            to,
+            max_climb,
+        );
+    }
+
+    /// Cheapest walk from `from` to `to` where every step costs 1 plus the height climbed.
+    pub fn least_effort_path(&self, from: GridPos, to: GridPos) -> Option<(Vec<GridPos>, usize)> {
+        let effort = |pos: &GridPos| {
+            let curr_height = self.get(pos.0, pos.1).height;
+            return grid_neighbours(*pos, self.width, self.height)
+                .into_iter()
+                .map(move |(row, col)| {
+                    let climb = self.get(row, col).height.saturating_sub(curr_height);
+                    return ((row, col), 1 + climb);
+                });
+        };
+        return dijkstra(from, effort, |pos| *pos == to);
+    }
+
+    /// Fewest steps from `from` to any tree on the edge of the grid climbing at most `max_climb`.
+    pub fn way_out(&self, from: GridPos, max_climb: usize) -> Option<Vec<GridPos>> {
+        let climbable = |pos: &GridPos| {
+            let max_height = self.get(pos.0, pos.1).height + max_climb;
+            return grid_neighbours(*pos, self.width, self.height)
+                .into_iter()
+                .filter(move |(row, col)| self.get(*row, *col).height <= max_height);
+        };
+        let on_edge = |(row, col): &GridPos| {
+            return *row == 0 || *col == 0 || *row + 1 == self.height || *col + 1 == self.width;
+        };
+        return bfs(from, climbable, on_edge);
+    }
+
+    /// Connected trees (up, down, left, right) no taller than the one at `from`.
+    pub fn grove_from(&self, from: GridPos) -> HashSet<GridPos> {
+        let max_height = self.get(from.0, from.1).height;
+        return grid_flood_fill(self.width, self.height, from, |(row, col)| {
+            return self.get(row, col).height <= max_height;
+        });
+    }
+}
+
+/// Draws the tree heights with the path cells replaced by `*`.
+pub fn render_path(trees: &TreeGrid, path: &[GridPos]) -> String {
+    let path = path.iter().collect::<HashSet<&GridPos>>();
+    let mut output = String::new();
+    for row in 0..trees.height {
+        for col in 0..trees.width {
+            if path.contains(&(row, col)) {
+                output.push('*');
+            } else {
+                output.push_str(&trees.get(row, col).height.to_string());
+            }
+        }
+        output.push('\n');
+    }
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_paths_on_sample() {
+        let trees = parse_tree_grid_from_file("./data/sample.txt");
+
+        let path = trees.climb_path((0, 0), (3, 3), 3).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(3, 3)));
+        assert!(path.windows(2).all(|step| {
+            let from = trees.get(step[0].0, step[0].1).height;
+            return trees.get(step[1].0, step[1].1).height <= from + 3;
+        }));
+        assert_eq!(trees.climb_path((0, 0), (3, 3), 2), None);
+
+        let (_, effort) = trees.least_effort_path((0, 1), (0, 0)).unwrap();
+        assert_eq!(effort, 4);
+
+        assert_eq!(trees.way_out((2, 2), 0).unwrap().len(), 3);
+        assert_eq!(trees.grove_from((0, 1)).len(), 1);
+    }
+}
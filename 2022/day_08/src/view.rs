@@ -0,0 +1,184 @@
+use crate::{ProbeDirection, TreeGrid, BOTTOM_UP, LEFT_RIGHT, RIGHT_LEFT, TOP_DOWN};
+
+#[derive(Debug, PartialEq)]
+pub struct SeenTree {
+    pub row: usize,
+    pub col: usize,
+    pub height: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SightLine {
+    pub direction: ProbeDirection,
+    /// trees in the order they are seen, the blocking one (if any) included
+    pub seen: Vec<SeenTree>,
+    pub blocked_by: Option<(usize, usize)>,
+    pub viewing_distance: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct View {
+    pub row: usize,
+    pub col: usize,
+    pub up: SightLine,
+    pub down: SightLine,
+    pub left: SightLine,
+    pub right: SightLine,
+}
+
+impl View {
+    pub fn scenic_score(&self) -> usize {
+        return self.up.viewing_distance
+            * self.down.viewing_distance
+            * self.left.viewing_distance
+            * self.right.viewing_distance;
+    }
+
+    pub fn sight_lines(&self) -> [&SightLine; 4] {
+        return [&self.up, &self.down, &self.left, &self.right];
+    }
+}
+
+impl TreeGrid {
+    pub fn view_from(&self, row: usize, col: usize) -> View {
+        return View {
+            row,
+            col,
+            up: self.look_from(row, col, BOTTOM_UP),
+            down: self.look_from(row, col, TOP_DOWN),
+            left: self.look_from(row, col, RIGHT_LEFT),
+            right: self.look_from(row, col, LEFT_RIGHT),
+        };
+    }
+
+    pub fn look_from(&self, row: usize, col: usize, direction: ProbeDirection) -> SightLine {
+        let (row_inc, col_inc) = direction_increments(direction);
+        let from_tree = self.get(row, col);
+
+        let mut seen = Vec::<SeenTree>::new();
+        let mut blocked_by = None;
+        let mut i = row as i32;
+        let mut j = col as i32;
+        loop {
+            i += row_inc;
+            j += col_inc;
+
+            if !self.contains(i, j) {
+                break;
+            }
+
+            let neighbor = self.get(i as usize, j as usize);
+            seen.push(SeenTree {
+                row: i as usize,
+                col: j as usize,
+                height: neighbor.height,
+            });
+            if neighbor.height >= from_tree.height {
+                blocked_by = Some((i as usize, j as usize));
+                break;
+            }
+        }
+
+        return SightLine {
+            direction,
+            viewing_distance: seen.len(),
+            seen,
+            blocked_by,
+        };
+    }
+
+    /// Same as `look_from(..).viewing_distance` without collecting the trees seen.
+    pub fn viewing_distance(&self, row: usize, col: usize, direction: ProbeDirection) -> usize {
+        let (row_inc, col_inc) = direction_increments(direction);
+        let from_height = self.get(row, col).height;
+
+        let mut distance = 0;
+        let mut i = row as i32 + row_inc;
+        let mut j = col as i32 + col_inc;
+        while self.contains(i, j) {
+            distance += 1;
+            if self.get(i as usize, j as usize).height >= from_height {
+                break;
+            }
+            i += row_inc;
+            j += col_inc;
+        }
+        return distance;
+    }
+
+    pub fn contains(&self, row: i32, col: i32) -> bool {
+        return row >= 0 && col >= 0 && row < self.height as i32 && col < self.width as i32;
+    }
+}
+
+pub fn direction_increments(direction: ProbeDirection) -> (i32, i32) {
+    return match direction {
+        BOTTOM_UP => (-1, 0),
+        TOP_DOWN => (1, 0),
+        LEFT_RIGHT => (0, 1),
+        RIGHT_LEFT => (0, -1),
+        _ => panic!("Invalid direction: {}", direction),
+    };
+}
+
+pub fn direction_name(direction: ProbeDirection) -> &'static str {
+    return match direction {
+        BOTTOM_UP => "up",
+        TOP_DOWN => "down",
+        LEFT_RIGHT => "right",
+        RIGHT_LEFT => "left",
+        _ => panic!("Invalid direction: {}", direction),
+    };
+}
+
+/// Draws the grid with the sight lines of `view` on top of it:
+/// `@` is the viewer, `|`/`-` are the trees seen, `X` is the tree blocking the view
+/// and every other tree is a `.`
+pub fn render_view(trees: &TreeGrid, view: &View) -> String {
+    let mut canvas = vec![vec!['.'; trees.width]; trees.height];
+    canvas[view.row][view.col] = '@';
+
+    for sight_line in view.sight_lines() {
+        let mark = match sight_line.direction {
+            BOTTOM_UP | TOP_DOWN => '|',
+            _ => '-',
+        };
+        for seen in &sight_line.seen {
+            canvas[seen.row][seen.col] = mark;
+        }
+        if let Some((row, col)) = sight_line.blocked_by {
+            canvas[row][col] = 'X';
+        }
+    }
+
+    let mut output = String::new();
+    for line in canvas {
+        output.extend(line);
+        output.push('\n');
+    }
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_view_from_sample() {
+        let trees = parse_tree_grid_from_file("./data/sample.txt");
+        let view = trees.view_from(3, 2);
+
+        assert_eq!(view.up.viewing_distance, 2);
+        assert_eq!(view.up.blocked_by, Some((1, 2)));
+        assert_eq!(view.left.viewing_distance, 2);
+        assert_eq!(view.left.blocked_by, None);
+        assert_eq!(view.down.viewing_distance, 1);
+        assert_eq!(view.right.viewing_distance, 2);
+        assert_eq!(view.right.blocked_by, Some((3, 4)));
+        assert_eq!(
+            view.left.seen.iter().map(|t| t.height).collect::<Vec<_>>(),
+            vec![3, 3]
+        );
+        assert_eq!(view.scenic_score(), 8);
+    }
+}
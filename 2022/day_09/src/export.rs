@@ -0,0 +1,109 @@
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::render::{Frame, FrameSink};
+
+/// Writes every frame as `frame_00001.ppm`, `frame_00002.ppm`, ... inside `dir`,
+/// each cell drawn as a `scale` x `scale` block.
+/// Use a follow viewport for a video, the bounding box one changes size as the rope moves.
+pub struct PpmSink {
+    dir: PathBuf,
+    scale: usize,
+    frame_count: usize,
+}
+
+impl PpmSink {
+    pub fn new(dir: PathBuf, scale: usize) -> Self {
+        create_dir_all(&dir).expect("Could not create the frames directory");
+        return Self {
+            dir,
+            scale: scale.max(1),
+            frame_count: 0,
+        };
+    }
+
+    pub fn frame_count(&self) -> usize {
+        return self.frame_count;
+    }
+}
+
+impl FrameSink for PpmSink {
+    fn show(&mut self, frame: &Frame) {
+        self.frame_count += 1;
+        let path = self.dir.join(format!("frame_{:05}.ppm", self.frame_count));
+        let file = File::create(&path).expect("Could not create frame file");
+        let mut writer = BufWriter::new(file);
+        write_ppm(&mut writer, frame, self.scale).expect("Could not write frame file");
+    }
+
+    fn finish(&mut self) {
+        println!(
+            "Wrote {} frames to {}",
+            self.frame_count,
+            self.dir.display()
+        );
+    }
+}
+
+/// Binary (P6) PPM of the frame.
+pub fn write_ppm(writer: &mut impl Write, frame: &Frame, scale: usize) -> std::io::Result<()> {
+    let width = frame.width * scale;
+    let height = frame.height * scale;
+    write!(writer, "P6\n{} {}\n255\n", width, height)?;
+
+    for row in frame.rows() {
+        let mut line = Vec::with_capacity(width * 3);
+        for &cell in row {
+            let color = cell_color(cell);
+            for _ in 0..scale {
+                line.extend_from_slice(&color);
+            }
+        }
+        for _ in 0..scale {
+            writer.write_all(&line)?;
+        }
+    }
+    return writer.flush();
+}
+
+pub fn cell_color(cell: char) -> [u8; 3] {
+    return match cell {
+        'H' => [230, 57, 70],
+        'T' => [69, 123, 157],
+        's' => [244, 211, 94],
+        '#' => [42, 110, 63],
+        '.' => [24, 24, 28],
+        // knots fade from the head color towards the tail one
+        digit if digit.is_ascii_digit() => {
+            let fade = digit.to_digit(10).unwrap() as u8 * 20;
+            [230 - fade, 100 + fade / 2, 120 + fade / 2]
+        }
+        _ => [200, 200, 200],
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::*;
+
+    #[test]
+    fn test_write_ppm() {
+        let frame = Frame {
+            title: None,
+            width: 2,
+            height: 1,
+            cells: vec!['H', '.'],
+        };
+        let mut bytes = Vec::<u8>::new();
+        write_ppm(&mut bytes, &frame, 2).unwrap();
+
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&bytes[..header.len()], header);
+        let pixels = &bytes[header.len()..];
+        assert_eq!(pixels.len(), 4 * 2 * 3);
+        assert_eq!(pixels[0..3], cell_color('H'));
+        assert_eq!(pixels[6..9], cell_color('.'));
+        assert_eq!(pixels[12..15], cell_color('H'));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{follow, Pos, RopeKnot};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    /// diagonal neighbours are 1 cell away
+    Chebyshev,
+    /// diagonal neighbours are 2 cells away
+    Manhattan,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Movement {
+    /// one cell on every axis that is behind (the puzzle rule)
+    Diagonal,
+    /// one cell on the axis that is the most behind
+    Orthogonal,
+}
+
+/// How a knot catches up with the one in front of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FollowRule {
+    pub metric: Metric,
+    /// how far the knot can be from the one in front of it without moving
+    pub slack: i64,
+    pub movement: Movement,
+    /// moves per tick, more than 1 makes the knot elastic (0 means as many as needed)
+    pub max_moves: u32,
+}
+
+impl Default for FollowRule {
+    fn default() -> Self {
+        return FollowRule::PUZZLE;
+    }
+}
+
+impl FollowRule {
+    pub const PUZZLE: FollowRule = FollowRule {
+        metric: Metric::Chebyshev,
+        slack: 1,
+        movement: Movement::Diagonal,
+        max_moves: 1,
+    };
+
+    /// Rule from `:` separated words on top of the puzzle rule:
+    /// `chebyshev`, `manhattan`, `slack=<n>`, `diagonal`, `orthogonal`, `elastic` or `elastic=<n>`.
+    /// e.g. `manhattan:slack=2:orthogonal`
+    pub fn parse(spec: &str) -> Self {
+        let mut rule = FollowRule::PUZZLE;
+        for word in spec.split(':').filter(|w| !w.is_empty()) {
+            let (name, value) = match word.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (word, None),
+            };
+            match (name, value) {
+                ("chebyshev", None) => rule.metric = Metric::Chebyshev,
+                ("manhattan", None) => rule.metric = Metric::Manhattan,
+                ("diagonal", None) => rule.movement = Movement::Diagonal,
+                ("orthogonal", None) => rule.movement = Movement::Orthogonal,
+                ("elastic", None) => rule.max_moves = 0,
+                ("elastic", Some(value)) => {
+                    rule.max_moves = value.parse::<u32>().expect("Invalid elastic moves!")
+                }
+                ("slack", Some(value)) => {
+                    rule.slack = value.parse::<i64>().expect("Invalid slack!");
+                    if rule.slack < 0 {
+                        panic!("Slack can not be negative: {}", spec);
+                    }
+                }
+                _ => panic!("Invalid follow rule '{}' in '{}'", word, spec),
+            }
+        }
+        return rule;
+    }
+
+    pub fn distance<const N: usize>(&self, delta: &Pos<N>) -> i64 {
+        let distances = delta.iter().map(|d| d.abs());
+        return match self.metric {
+            Metric::Chebyshev => distances.max().unwrap_or(0),
+            Metric::Manhattan => distances.sum(),
+        };
+    }
+
+    pub fn should_follow<const N: usize>(&self, delta: &Pos<N>) -> bool {
+        return self.distance(delta) > self.slack;
+    }
+
+    /// Single move towards the knot in front, `delta` being the offset to it.
+    pub fn next_move<const N: usize>(&self, delta: &Pos<N>) -> Pos<N> {
+        return match self.movement {
+            Movement::Diagonal => follow(*delta),
+            Movement::Orthogonal => {
+                let mut step = [0; N];
+                let furthest = (0..N).rev().max_by_key(|&axis| delta[axis].abs());
+                if let Some(axis) = furthest {
+                    step[axis] = delta[axis].signum();
+                }
+                step
+            }
+        };
+    }
+
+    /// Moves `knot` towards `leader`, returns if it moved at all.
+    pub fn catch_up<const N: usize>(&self, leader: &Pos<N>, knot: &mut RopeKnot<N>) -> bool {
+        let mut moves = 0;
+        loop {
+            let mut delta = [0; N];
+            for (axis, d) in delta.iter_mut().enumerate() {
+                *d = leader[axis] - knot.pos[axis];
+            }
+            if !self.should_follow(&delta) || (self.max_moves > 0 && moves == self.max_moves) {
+                return moves > 0;
+            }
+
+            for (axis, d) in self.next_move(&delta).iter().enumerate() {
+                knot.pos[axis] += d;
+            }
+            moves += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::follow_rule::*;
+
+    #[test]
+    fn test_rule_variants() {
+        let leader = [3, 1];
+
+        let mut knot = RopeKnot { pos: [0, 0] };
+        assert!(FollowRule::PUZZLE.catch_up(&leader, &mut knot));
+        assert_eq!(knot.pos, [1, 1]);
+
+        let mut knot = RopeKnot { pos: [0, 0] };
+        FollowRule::parse("orthogonal").catch_up(&leader, &mut knot);
+        assert_eq!(knot.pos, [1, 0]);
+
+        let mut knot = RopeKnot { pos: [0, 0] };
+        FollowRule::parse("elastic").catch_up(&leader, &mut knot);
+        assert_eq!(knot.pos, [2, 1]);
+
+        let mut knot = RopeKnot { pos: [0, 0] };
+        assert!(!FollowRule::parse("slack=3").catch_up(&leader, &mut knot));
+        assert!(FollowRule::parse("manhattan:slack=3").catch_up(&leader, &mut knot));
+    }
+}
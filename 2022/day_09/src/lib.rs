@@ -1,45 +1,138 @@
-type Coord = (i32, i32);
+pub mod export;
+pub mod follow_rule;
+pub mod playback;
+pub mod render;
+pub mod rope;
+pub mod stats;
+pub mod visited;
 
-pub struct RopeKnot {
-    pub x: i32,
-    pub y: i32,
+/// Position on an `N` dimensional grid (x, y, z, ...).
+pub type Pos<const N: usize> = [i64; N];
+
+pub struct RopeKnot<const N: usize = 2> {
+    pub pos: Pos<N>,
 }
 
-impl RopeKnot {
+impl<const N: usize> RopeKnot<N> {
     pub fn new() -> Self {
-        return Self { x: 0, y: 0 };
+        return Self { pos: [0; N] };
     }
-}
 
-pub fn parse_move_cmd(line: String) -> (char, u32) {
-    let mut chars = line.split_whitespace();
-    let letter: char;
-    let count: u32;
+    /// Coordinate on `axis`, 0 when the knot does not have that many dimensions.
+    pub fn axis(&self, axis: usize) -> i64 {
+        return self.pos.get(axis).copied().unwrap_or(0);
+    }
+}
 
-    if let Some(direction_string) = chars.next() {
-        letter = direction_string.chars().next().expect("Invalid direction");
-    } else {
-        panic!("Could not read direction from line: {}.", line)
+impl<const N: usize> Default for RopeKnot<N> {
+    fn default() -> Self {
+        return Self::new();
     }
+}
+
+/// Head movement read from the input: `R`/`L` on x, `U`/`D` on y, `F`/`B` on z
+/// and `+<axis>`/`-<axis>` (0 based) for any axis, e.g. `-3 5` moves 5 times on the fourth one.
+pub struct MoveCmd<const N: usize> {
+    pub direction: String,
+    pub step: Pos<N>,
+    pub count: u32,
+}
+
+impl<const N: usize> MoveCmd<N> {
+    pub fn parse(line: &str) -> Self {
+        let mut split = line.split_whitespace();
+        let direction = match split.next() {
+            Some(direction) => direction,
+            None => panic!("Could not read direction from line: {}.", line),
+        };
+
+        let count = match split.next() {
+            Some(count_string) => count_string
+                .parse::<u32>()
+                .expect("Invalid movement count!"),
+            None => panic!("Could not read movement count from line: {}.", line),
+        };
+
+        let (axis, sign) = match direction {
+            "R" => (0, 1),
+            "L" => (0, -1),
+            "U" => (1, 1),
+            "D" => (1, -1),
+            "F" => (2, 1),
+            "B" => (2, -1),
+            _ => {
+                let sign = match direction.chars().next() {
+                    Some('+') => 1,
+                    Some('-') => -1,
+                    _ => panic!("Invalid direction '{}' on line: {}.", direction, line),
+                };
+                let axis = direction[1..]
+                    .parse::<usize>()
+                    .expect("Invalid axis number!");
+                (axis, sign)
+            }
+        };
+
+        if axis >= N {
+            panic!(
+                "Direction '{}' needs at least {} dimensions, the rope has {}.",
+                direction,
+                axis + 1,
+                N
+            );
+        }
 
-    if let Some(count_string) = chars.next() {
-        count = count_string
-            .parse::<u32>()
-            .expect("Invalid movement count!");
-    } else {
-        panic!("Could not read movement count from line: {}.", line)
+        let mut step = [0; N];
+        step[axis] = sign;
+        return Self {
+            direction: direction.to_string(),
+            step,
+            count,
+        };
     }
-    return (letter, count);
 }
 
-pub fn calc_distance(prev_knot: &Coord, knot: &RopeKnot) -> (bool, Coord) {
-    let dx = prev_knot.0 - knot.x;
-    let dy = prev_knot.1 - knot.y;
+pub fn calc_distance<const N: usize>(prev_knot: &Pos<N>, knot: &RopeKnot<N>) -> (bool, Pos<N>) {
+    let mut delta = [0; N];
+    for (axis, d) in delta.iter_mut().enumerate() {
+        *d = prev_knot[axis] - knot.pos[axis];
+    }
     // using square to remove signal
-    let should_follow = dx * dx > 1 || dy * dy > 1;
-    return (should_follow, (dx, dy));
+    let should_follow = delta.iter().any(|d| d * d > 1);
+    return (should_follow, delta);
+}
+
+pub fn follow<const N: usize>(delta: Pos<N>) -> Pos<N> {
+    return delta.map(i64::signum);
 }
 
-pub fn follow(x: i32, y: i32) -> Coord {
-    return (x.signum(), y.signum());
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_parse_extended_moves() {
+        let cmd = MoveCmd::<3>::parse("B 7");
+        assert_eq!(cmd.step, [0, 0, -1]);
+        assert_eq!(cmd.count, 7);
+        assert_eq!(MoveCmd::<4>::parse("+3 2").step, [0, 0, 0, 1]);
+        assert_eq!(MoveCmd::<1>::parse("L 1").step, [-1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_move_outside_dimensions() {
+        MoveCmd::<2>::parse("F 1");
+    }
+
+    #[test]
+    fn test_follow_in_3d() {
+        let knot = RopeKnot::<3> { pos: [0, 0, 0] };
+        let (should_follow, delta) = calc_distance(&[1, 2, -1], &knot);
+        assert!(should_follow);
+        assert_eq!(follow(delta), [1, 1, -1]);
+
+        let (should_follow, _) = calc_distance(&[1, 1, 1], &knot);
+        assert!(!should_follow);
+    }
 }
@@ -1,97 +1,272 @@
-use std::collections::HashSet;
+use std::io::stdout;
+use std::path::PathBuf;
 
+use day_09::export::PpmSink;
+use day_09::follow_rule::FollowRule;
+use day_09::playback::PlaybackSink;
+use day_09::render::{FrameSink, PrintSink, RenderMode, Renderer, Viewport};
+use day_09::rope::Rope;
+use day_09::stats::render_heatmap;
 use day_09::*;
-use utils::io::yield_lines_trimmed;
-
-type Coord = (i32, i32);
+use utils::io::{flag_value, has_flag, yield_lines_trimmed};
 
 fn main() {
-    let (input_file, verbose) = utils::io::parse_args();
-    let part_one = solve(&input_file, 1, verbose);
-    println!("Part one: {:?}", part_one);
-    let part_two = solve(&input_file, 9, verbose);
-    println!("Part two: {:?}", part_two);
+    let (input_file, verbose, mode, mode_args) = utils::io::parse_args_with_mode();
+    let (default_frames, sink): (RenderMode, Box<dyn FrameSink>) = match mode.as_deref() {
+        None => {
+            let dims = parse_dims_flag(&mode_args);
+            let fast_forward = !has_flag(&mode_args, "step-by-step");
+            let rules = parse_rules_flag(&mode_args, 1);
+            let part_one = simulate_in(dims, &input_file, &rules, verbose, &mut None, fast_forward);
+            println!("Part one: {:?}", part_one);
+            let rules = parse_rules_flag(&mode_args, 9);
+            let part_two = simulate_in(dims, &input_file, &rules, verbose, &mut None, fast_forward);
+            println!("Part two: {:?}", part_two);
+            return;
+        }
+        Some("render") => (RenderMode::EveryCommand, Box::new(PrintSink)),
+        Some("stats") => {
+            let rules = parse_rules_flag(&mode_args, parse_knots_flag(&mode_args));
+            match parse_dims_flag(&mode_args) {
+                1 => print_stats::<1>(&input_file, &rules, verbose, &mode_args),
+                2 => print_stats::<2>(&input_file, &rules, verbose, &mode_args),
+                3 => print_stats::<3>(&input_file, &rules, verbose, &mode_args),
+                4 => print_stats::<4>(&input_file, &rules, verbose, &mode_args),
+                dims => panic!("Only 1 to 4 dimensions are available, got {}", dims),
+            }
+            return;
+        }
+        Some("play") => {
+            let fps = match flag_value(&mode_args, "fps") {
+                Some(value) => value.parse::<u32>().expect("Invalid --fps!"),
+                None => 10,
+            };
+            (RenderMode::EveryStep, Box::new(PlaybackSink::new(fps)))
+        }
+        Some("export") => {
+            let dir = PathBuf::from(flag_value(&mode_args, "out").unwrap_or("frames"));
+            let scale = match flag_value(&mode_args, "scale") {
+                Some(value) => value.parse::<usize>().expect("Invalid --scale!"),
+                None => 8,
+            };
+            (RenderMode::EveryStep, Box::new(PpmSink::new(dir, scale)))
+        }
+        Some(mode) => {
+            eprintln!(
+                "Unknown mode '{}' (available: render, play, export, stats)",
+                mode
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let renderer = parse_renderer(&mode_args, default_frames, sink);
+    let rules = parse_rules_flag(&mode_args, parse_knots_flag(&mode_args));
+    let dims = parse_dims_flag(&mode_args);
+    let fast_forward = !has_flag(&mode_args, "step-by-step");
+    let tail_visited = simulate_in(
+        dims,
+        &input_file,
+        &rules,
+        verbose,
+        &mut Some(renderer),
+        fast_forward,
+    );
+    println!("Tail visited: {}", tail_visited);
 }
 
 pub fn solve(input_file: &str, knots_count: usize, verbose: bool) -> usize {
-    let mut head = RopeKnot::new();
-    let mut knots = (0..knots_count)
-        .map(|_| RopeKnot::new())
-        .collect::<Vec<RopeKnot>>();
-    let mut tail_visited_positions = HashSet::<(i32, i32)>::new();
+    let rules = vec![FollowRule::PUZZLE; knots_count];
+    return simulate::<2>(input_file, &rules, verbose, &mut None, true);
+}
 
-    // starting position
-    tail_visited_positions.insert((0, 0));
+/// Runs `simulate` for a dimension only known at runtime.
+pub fn simulate_in(
+    dims: usize,
+    input_file: &str,
+    rules: &[FollowRule],
+    verbose: bool,
+    renderer: &mut Option<Renderer>,
+    fast_forward: bool,
+) -> usize {
+    return match dims {
+        1 => simulate::<1>(input_file, rules, verbose, renderer, fast_forward),
+        2 => simulate::<2>(input_file, rules, verbose, renderer, fast_forward),
+        3 => simulate::<3>(input_file, rules, verbose, renderer, fast_forward),
+        4 => simulate::<4>(input_file, rules, verbose, renderer, fast_forward),
+        _ => panic!("Only 1 to 4 dimensions are available, got {}", dims),
+    };
+}
+
+/// Simulates a rope with one knot per rule (besides the head), each following the one in front
+/// of it with its own rule.
+pub fn simulate<const N: usize>(
+    input_file: &str,
+    rules: &[FollowRule],
+    verbose: bool,
+    renderer: &mut Option<Renderer>,
+    fast_forward: bool,
+) -> usize {
+    let mut rope = Rope::<N>::new(rules);
+    run(input_file, &mut rope, verbose, renderer, fast_forward);
+    return rope.tail_visited.len();
+}
+
+pub fn run<const N: usize>(
+    input_file: &str,
+    rope: &mut Rope<N>,
+    verbose: bool,
+    renderer: &mut Option<Renderer>,
+    fast_forward: bool,
+) {
+    if let Some(renderer) = renderer {
+        renderer.start(rope);
+    }
 
     for line in yield_lines_trimmed(&input_file) {
-        let (direction, count) = parse_move_cmd(line);
-        apply(
-            direction,
-            count,
-            &mut head,
-            &mut knots,
-            &mut tail_visited_positions,
-            verbose,
-        );
+        let move_cmd = MoveCmd::<N>::parse(&line);
+        apply(&move_cmd, rope, verbose, renderer, fast_forward);
     }
 
-    return tail_visited_positions.len();
+    if let Some(renderer) = renderer {
+        renderer.finish(rope);
+    }
 }
 
-pub fn apply(
-    direction: char,
-    count: u32,
-    head: &mut RopeKnot,
-    knots: &mut Vec<RopeKnot>,
-    tail_history: &mut HashSet<(i32, i32)>,
+/// Moves the head `count` times. With `fast_forward` the remaining moves are done at once as
+/// soon as the rope moves rigidly (unless every step has to be rendered).
+pub fn apply<const N: usize>(
+    move_cmd: &MoveCmd<N>,
+    rope: &mut Rope<N>,
     verbose: bool,
+    renderer: &mut Option<Renderer>,
+    fast_forward: bool,
 ) {
-    let move_cmd = match direction {
-        'U' => (0, 1),
-        'D' => (0, -1),
-        'L' => (-1, 0),
-        'R' => (1, 0),
-        _ => panic!("Invalid direction!"),
-    };
+    let fast_forward = fast_forward
+        && rope.stats.is_none()
+        && renderer
+            .as_ref()
+            .is_none_or(|renderer| renderer.mode != RenderMode::EveryStep);
 
-    for _ in 0..count {
-        head.x += move_cmd.0;
-        head.y += move_cmd.1;
+    let command = format!("{} {}", move_cmd.direction, move_cmd.count);
+    for step in 1..=move_cmd.count {
+        let rigid = rope.step(&move_cmd.step);
 
         if verbose {
-            println!(
-                "Moving head {}{} -> ({}, {})",
-                direction, count, head.x, head.y
-            );
+            println!("Moving head {} -> {:?}", command, rope.head.pos);
+            for (i, knot) in rope.knots.iter().enumerate() {
+                println!("Knot {}: {:?}", i, knot.pos);
+            }
+            println!();
         }
 
-        let tail_position = advance_knots(knots, head.x, head.y);
-        tail_history.insert(tail_position);
+        if let Some(renderer) = renderer {
+            renderer.step(&command, step, rope);
+        }
 
-        if verbose {
-            for (i, knot) in knots.iter().enumerate() {
-                println!("Knot {}: ({}, {})", i, knot.x, knot.y);
+        if rigid && fast_forward && step < move_cmd.count {
+            let remaining = move_cmd.count - step;
+            if verbose {
+                println!("Rope is straight, fast-forwarding {} steps", remaining);
             }
-            println!();
+            rope.shift(&move_cmd.step, remaining);
+            break;
         }
     }
+
+    if let Some(renderer) = renderer {
+        renderer.command_done(&command, rope);
+    }
 }
 
-fn advance_knots(knots: &mut Vec<RopeKnot>, head_x: i32, head_y: i32) -> Coord {
-    let mut prev_knot = (head_x, head_y);
-    for knot in knots {
-        let (is_separated, (dx, dy)) = calc_distance(&prev_knot, knot);
-        if !is_separated {
-            prev_knot = (knot.x, knot.y);
-            continue;
-        }
+/// Per knot summary, `--heatmap <knot label>` draws the visits of a knot (`all` for every one)
+/// and `--csv` prints the visits of every knot on every cell instead.
+fn print_stats<const N: usize>(
+    input_file: &str,
+    rules: &[FollowRule],
+    verbose: bool,
+    mode_args: &[String],
+) {
+    let mut rope = Rope::<N>::with_stats(rules);
+    run(input_file, &mut rope, verbose, &mut None, false);
+    let stats = rope.stats.expect("Rope created with stats");
+
+    if has_flag(mode_args, "csv") {
+        stats
+            .write_csv(&mut stdout().lock())
+            .expect("Could not write csv to stdout");
+        return;
+    }
 
-        let (dx, dy) = follow(dx, dy);
-        knot.x += dx;
-        knot.y += dy;
-        prev_knot = (knot.x, knot.y);
+    stats.print_summary();
+    if let Some(knot) = flag_value(mode_args, "heatmap") {
+        let mut sink = PrintSink;
+        println!();
+        for i in 0..stats.knots.len() {
+            let label = stats.label(i);
+            if knot == "all" || knot == label {
+                sink.show(&render_heatmap(&stats.knots[i], label));
+            }
+        }
     }
-    return prev_knot; // not moved
+}
+
+fn parse_dims_flag(mode_args: &[String]) -> usize {
+    return match flag_value(mode_args, "dims") {
+        Some(value) => value.parse::<usize>().expect("Invalid --dims count!"),
+        None => 2,
+    };
+}
+
+/// `--rules <rule>,<rule>,...` one `FollowRule::parse` spec per knot, the last one is repeated
+/// for the remaining knots (default is the puzzle rule).
+fn parse_rules_flag(mode_args: &[String], knots_count: usize) -> Vec<FollowRule> {
+    let specs = match flag_value(mode_args, "rules") {
+        Some(value) => value.split(',').map(FollowRule::parse).collect(),
+        None => vec![FollowRule::PUZZLE],
+    };
+    return (0..knots_count)
+        .map(|i| specs[i.min(specs.len() - 1)])
+        .collect();
+}
+
+fn parse_knots_flag(mode_args: &[String]) -> usize {
+    return match flag_value(mode_args, "knots") {
+        Some(value) => value.parse::<usize>().expect("Invalid --knots count!"),
+        None => 9,
+    };
+}
+
+/// `--frames step|command|final`, `--viewport box|follow` (default box)
+/// and `--size <width>x<height>` for the follow viewport (default 26x21).
+fn parse_renderer(
+    mode_args: &[String],
+    default_mode: RenderMode,
+    sink: Box<dyn FrameSink>,
+) -> Renderer {
+    let mode = match flag_value(mode_args, "frames") {
+        None => default_mode,
+        Some("step") => RenderMode::EveryStep,
+        Some("command") => RenderMode::EveryCommand,
+        Some("final") => RenderMode::FinalState,
+        Some(other) => panic!("Invalid --frames '{}' (use step, command or final)", other),
+    };
+
+    let viewport = match flag_value(mode_args, "viewport") {
+        Some("box") | None => Viewport::BoundingBox,
+        Some("follow") => {
+            let size = flag_value(mode_args, "size").unwrap_or("26x21");
+            let (width, height) = size
+                .split_once('x')
+                .map(|(w, h)| (w.parse::<usize>(), h.parse::<usize>()))
+                .and_then(|(w, h)| Some((w.ok()?, h.ok()?)))
+                .expect("Invalid --size (use <width>x<height>)");
+            Viewport::FollowRope { width, height }
+        }
+        Some(other) => panic!("Invalid --viewport '{}' (use box or follow)", other),
+    };
+
+    return Renderer::new(mode, viewport, sink);
 }
 
 #[cfg(test)]
@@ -109,4 +284,28 @@ mod tests {
         let part_two = solve("./data/input.txt", 9, false);
         assert_eq!(part_two, 2531);
     }
+
+    #[test]
+    fn test_fast_forward_matches_step_by_step() {
+        let variants = [
+            vec![FollowRule::PUZZLE; 9],
+            vec![FollowRule::parse("orthogonal"); 4],
+            vec![
+                FollowRule::parse("manhattan:slack=2:elastic"),
+                FollowRule::parse("slack=3"),
+                FollowRule::PUZZLE,
+            ],
+        ];
+        for rules in variants {
+            let fast = simulate::<2>("./data/input.txt", &rules, false, &mut None, true);
+            let slow = simulate::<2>("./data/input.txt", &rules, false, &mut None, false);
+            assert_eq!(fast, slow);
+        }
+    }
+
+    #[test]
+    fn test_huge_moves() {
+        let part_one = solve("./data/huge.txt", 1, false);
+        assert_eq!(part_one, 2000000001);
+    }
 }
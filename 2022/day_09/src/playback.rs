@@ -0,0 +1,137 @@
+use std::io::{stdin, stdout, Write};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::render::{Frame, FrameSink};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    TogglePause,
+    Step,
+    Faster,
+    Slower,
+    Quit,
+}
+
+impl Control {
+    pub fn parse(line: &str) -> Option<Self> {
+        return match line.trim() {
+            "" | "p" => Some(Control::TogglePause),
+            "s" | "n" => Some(Control::Step),
+            "+" => Some(Control::Faster),
+            "-" => Some(Control::Slower),
+            "q" => Some(Control::Quit),
+            _ => None,
+        };
+    }
+}
+
+/// Redraws the terminal in place for every frame.
+/// Controls are read line by line from stdin: enter or `p` pauses/resumes, `s` steps a
+/// single frame while paused, `+`/`-` change the frame rate and `q` skips to the end.
+pub struct PlaybackSink {
+    frame_delay: Duration,
+    paused: bool,
+    quit: bool,
+    frame_count: usize,
+    controls: Receiver<Control>,
+}
+
+impl PlaybackSink {
+    pub fn new(fps: u32) -> Self {
+        let (sender, controls) = channel();
+        thread::spawn(move || {
+            for line in stdin().lines() {
+                let Ok(line) = line else { break };
+                if let Some(control) = Control::parse(&line) {
+                    if sender.send(control).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        return Self {
+            frame_delay: fps_to_delay(fps),
+            paused: false,
+            quit: false,
+            frame_count: 0,
+            controls,
+        };
+    }
+
+    fn handle(&mut self, control: Control) -> bool {
+        match control {
+            Control::TogglePause => self.paused = !self.paused,
+            Control::Faster => self.frame_delay = (self.frame_delay / 2).max(MIN_FRAME_DELAY),
+            Control::Slower => self.frame_delay *= 2,
+            Control::Quit => self.quit = true,
+            Control::Step => return true,
+        }
+        return false;
+    }
+
+    fn wait_for_next_frame(&mut self) {
+        // drain the controls typed while the frame was drawn
+        while let Ok(control) = self.controls.try_recv() {
+            self.handle(control);
+        }
+
+        while self.paused && !self.quit {
+            match self.controls.recv() {
+                Ok(control) => {
+                    if self.handle(control) {
+                        return; // step a single frame
+                    }
+                }
+                Err(_) => self.paused = false, // stdin closed, nothing can resume us
+            }
+        }
+
+        if self.quit {
+            return;
+        }
+        match self.controls.recv_timeout(self.frame_delay) {
+            Ok(control) => {
+                self.handle(control);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(self.frame_delay),
+        }
+    }
+}
+
+impl FrameSink for PlaybackSink {
+    fn show(&mut self, frame: &Frame) {
+        if self.quit {
+            return;
+        }
+        self.frame_count += 1;
+
+        // clear the screen and move the cursor home
+        let mut output = String::from("\x1b[2J\x1b[H");
+        output.push_str(&frame.to_string());
+        output.push_str(&format!(
+            "\nframe {} | {:?} per frame | {}\n",
+            self.frame_count,
+            self.frame_delay,
+            if self.paused {
+                "paused (enter/p resume, s step, q quit)"
+            } else {
+                "playing (enter/p pause, +/- speed, q quit)"
+            }
+        ));
+        print!("{}", output);
+        stdout().flush().expect("Could not flush stdout");
+
+        self.wait_for_next_frame();
+    }
+}
+
+/// Halving stops here, a zero delay could never be doubled back.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(1);
+
+fn fps_to_delay(fps: u32) -> Duration {
+    return Duration::from_secs_f64(1.0 / fps.max(1) as f64).max(MIN_FRAME_DELAY);
+}
@@ -0,0 +1,276 @@
+use crate::rope::Rope;
+use crate::visited::VisitedCells;
+use crate::{Pos, RopeKnot};
+
+type Coord = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    EveryStep,
+    EveryCommand,
+    FinalState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Viewport {
+    /// fixed size window centered on the head
+    FollowRope { width: usize, height: usize },
+    /// everything the rope touched so far
+    BoundingBox,
+}
+
+/// Rendered rope state, rows top to bottom (highest `y` first).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub title: Option<String>,
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<char>,
+}
+
+impl Frame {
+    pub fn rows(&self) -> impl Iterator<Item = &[char]> {
+        return self.cells.chunks(self.width);
+    }
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(title) = &self.title {
+            writeln!(f, "== {} ==", title)?;
+            writeln!(f)?;
+        }
+        for row in self.rows() {
+            writeln!(f, "{}", row.iter().collect::<String>())?;
+        }
+        return Ok(());
+    }
+}
+
+/// Where the rendered frames go.
+pub trait FrameSink {
+    fn show(&mut self, frame: &Frame);
+
+    fn finish(&mut self) {}
+}
+
+pub struct PrintSink;
+
+impl FrameSink for PrintSink {
+    fn show(&mut self, frame: &Frame) {
+        println!("{}", frame);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds {
+    pub min_x: i64,
+    pub max_x: i64,
+    pub min_y: i64,
+    pub max_y: i64,
+}
+
+impl Bounds {
+    pub fn around(coord: Coord) -> Self {
+        return Self {
+            min_x: coord.0,
+            max_x: coord.0,
+            min_y: coord.1,
+            max_y: coord.1,
+        };
+    }
+
+    pub fn include(&mut self, coord: Coord) {
+        self.min_x = self.min_x.min(coord.0);
+        self.max_x = self.max_x.max(coord.0);
+        self.min_y = self.min_y.min(coord.1);
+        self.max_y = self.max_y.max(coord.1);
+    }
+
+    pub fn width(&self) -> usize {
+        return (self.max_x - self.min_x + 1) as usize;
+    }
+
+    pub fn height(&self) -> usize {
+        return (self.max_y - self.min_y + 1) as usize;
+    }
+}
+
+/// Decides which steps of the simulation become frames and hands them to a `FrameSink`.
+pub struct Renderer {
+    pub mode: RenderMode,
+    pub viewport: Viewport,
+    seen: Bounds,
+    sink: Box<dyn FrameSink>,
+}
+
+impl Renderer {
+    pub fn new(mode: RenderMode, viewport: Viewport, sink: Box<dyn FrameSink>) -> Self {
+        return Self {
+            mode,
+            viewport,
+            seen: Bounds::around((0, 0)),
+            sink,
+        };
+    }
+
+    pub fn start<const N: usize>(&mut self, rope: &Rope<N>) {
+        self.track(rope);
+        if self.mode != RenderMode::FinalState {
+            self.draw(Some("Initial State".to_string()), rope);
+        }
+    }
+
+    /// Called after every head move, `step` starting at 1 for each command.
+    pub fn step<const N: usize>(&mut self, command: &str, step: u32, rope: &Rope<N>) {
+        self.track(rope);
+        if self.mode == RenderMode::EveryStep {
+            let title = if step == 1 {
+                Some(command.to_string())
+            } else {
+                None
+            };
+            self.draw(title, rope);
+        }
+    }
+
+    pub fn command_done<const N: usize>(&mut self, command: &str, rope: &Rope<N>) {
+        self.track(rope);
+        if self.mode == RenderMode::EveryCommand {
+            self.draw(Some(command.to_string()), rope);
+        }
+    }
+
+    pub fn finish<const N: usize>(&mut self, rope: &Rope<N>) {
+        if self.mode == RenderMode::FinalState {
+            self.draw(Some("Final State".to_string()), rope);
+        }
+        self.sink.finish();
+    }
+
+    fn track<const N: usize>(&mut self, rope: &Rope<N>) {
+        self.seen.include(project(&rope.head.pos));
+        for knot in &rope.knots {
+            self.seen.include(project(&knot.pos));
+        }
+    }
+
+    fn draw<const N: usize>(&mut self, title: Option<String>, rope: &Rope<N>) {
+        let bounds = match self.viewport {
+            Viewport::BoundingBox => self.seen,
+            Viewport::FollowRope { width, height } => {
+                let min_x = rope.head.axis(0) - width as i64 / 2;
+                let min_y = rope.head.axis(1) - height as i64 / 2;
+                Bounds {
+                    min_x,
+                    max_x: min_x + width as i64 - 1,
+                    min_y,
+                    max_y: min_y + height as i64 - 1,
+                }
+            }
+        };
+        let mut frame = render_frame(&bounds, &rope.head, &rope.knots, &rope.tail_visited);
+        frame.title = title;
+        self.sink.show(&frame);
+    }
+}
+
+/// Draws the rope inside `bounds` like the puzzle does: `H` for the head, `1`..`9` for the
+/// knots (`T` when there is a single one or for the tail of longer ropes), `s` for the start
+/// and `#` for cells visited by the tail. Knots closer to the head are drawn on top.
+/// Ropes with other than 2 dimensions are projected on the x/y plane.
+pub fn render_frame<const N: usize>(
+    bounds: &Bounds,
+    head: &RopeKnot<N>,
+    knots: &[RopeKnot<N>],
+    visited: &VisitedCells<N>,
+) -> Frame {
+    let width = bounds.width();
+    let height = bounds.height();
+    let mut cells = vec!['.'; width * height];
+
+    let mut paint = |coord: Coord, mark: char| {
+        if coord.0 < bounds.min_x
+            || coord.0 > bounds.max_x
+            || coord.1 < bounds.min_y
+            || coord.1 > bounds.max_y
+        {
+            return;
+        }
+        let row = (bounds.max_y - coord.1) as usize;
+        let col = (coord.0 - bounds.min_x) as usize;
+        cells[row * width + col] = mark;
+    };
+
+    for coord in visited.cells() {
+        paint(project(coord), '#');
+    }
+    for (axis, base, from, to) in visited.segments() {
+        if axis > 1 {
+            // perpendicular to the x/y plane, the whole run is a single cell
+            paint(project(&base), '#');
+            continue;
+        }
+        let (min, max) = if axis == 0 {
+            (bounds.min_x, bounds.max_x)
+        } else {
+            (bounds.min_y, bounds.max_y)
+        };
+        let mut cell = base;
+        for value in from.max(min)..=to.min(max) {
+            cell[axis] = value;
+            paint(project(&cell), '#');
+        }
+    }
+    paint((0, 0), 's');
+    for (i, knot) in knots.iter().enumerate().rev() {
+        paint(project(&knot.pos), knot_label(i, knots.len()));
+    }
+    paint(project(&head.pos), 'H');
+
+    return Frame {
+        title: None,
+        width,
+        height,
+        cells,
+    };
+}
+
+/// x/y plane coordinate of `pos`, missing axes are 0.
+pub fn project<const N: usize>(pos: &Pos<N>) -> Coord {
+    return (
+        pos.first().copied().unwrap_or(0),
+        pos.get(1).copied().unwrap_or(0),
+    );
+}
+
+pub fn knot_label(index: usize, knots_count: usize) -> char {
+    let is_tail = index + 1 == knots_count;
+    if is_tail && (knots_count == 1 || knots_count > 9) {
+        return 'T';
+    }
+    return char::from_digit(index as u32 + 1, 10).unwrap_or('*');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::*;
+
+    #[test]
+    fn test_render_frame_like_the_puzzle() {
+        let head = RopeKnot { pos: [4, 0] };
+        let knots = [RopeKnot { pos: [3, 0] }];
+        let mut visited = VisitedCells::new();
+        visited.insert([0, 0]);
+        visited.insert_segment([1, 0], 0, 2);
+        let bounds = Bounds {
+            min_x: 0,
+            max_x: 5,
+            min_y: 0,
+            max_y: 1,
+        };
+
+        let frame = render_frame(&bounds, &head, &knots, &visited);
+        assert_eq!(frame.to_string(), "......\ns##TH.\n");
+    }
+}
@@ -0,0 +1,97 @@
+use crate::follow_rule::FollowRule;
+use crate::stats::RopeStats;
+use crate::visited::VisitedCells;
+use crate::{Pos, RopeKnot};
+
+pub struct Rope<const N: usize> {
+    pub head: RopeKnot<N>,
+    pub knots: Vec<RopeKnot<N>>,
+    /// one rule per knot
+    pub rules: Vec<FollowRule>,
+    pub tail_visited: VisitedCells<N>,
+    /// per knot statistics, only collected when asked for since they need every step
+    pub stats: Option<RopeStats<N>>,
+}
+
+impl<const N: usize> Rope<N> {
+    pub fn new(rules: &[FollowRule]) -> Self {
+        let mut tail_visited = VisitedCells::new();
+        // starting position
+        tail_visited.insert([0; N]);
+
+        return Self {
+            head: RopeKnot::new(),
+            knots: rules.iter().map(|_| RopeKnot::new()).collect(),
+            rules: rules.to_vec(),
+            tail_visited,
+            stats: None,
+        };
+    }
+
+    pub fn with_stats(rules: &[FollowRule]) -> Self {
+        let mut rope = Self::new(rules);
+        rope.stats = Some(RopeStats::new(rules.len()));
+        return rope;
+    }
+
+    pub fn tail(&self) -> Pos<N> {
+        return match self.knots.last() {
+            Some(knot) => knot.pos,
+            None => self.head.pos,
+        };
+    }
+
+    /// Moves the head one cell and lets every knot catch up.
+    /// Returns if the whole rope moved rigidly, that is every knot moved exactly like the head.
+    pub fn step(&mut self, step: &Pos<N>) -> bool {
+        add(&mut self.head.pos, step, 1);
+
+        let mut rigid = true;
+        let mut prev_knot = self.head.pos;
+        for (knot, rule) in self.knots.iter_mut().zip(&self.rules) {
+            let before = knot.pos;
+            rule.catch_up(&prev_knot, knot);
+            rigid &= (0..N).all(|axis| knot.pos[axis] - before[axis] == step[axis]);
+            prev_knot = knot.pos;
+        }
+
+        self.tail_visited.insert(self.tail());
+        if let Some(stats) = &mut self.stats {
+            stats.knots[0].record(self.head.pos);
+            for (knot_stats, knot) in stats.knots[1..].iter_mut().zip(&self.knots) {
+                knot_stats.record(knot.pos);
+            }
+        }
+        return rigid;
+    }
+
+    /// Same as calling `step` `ticks` times, but only valid right after a rigid step:
+    /// the knots rules only look at the offsets between knots, so once a step moved every knot
+    /// like the head the next ones will too and the tail just draws a line.
+    pub fn shift(&mut self, step: &Pos<N>, ticks: u32) {
+        if ticks == 0 {
+            return;
+        }
+        if self.stats.is_some() {
+            panic!("Can not shift a rope collecting stats, they need every step");
+        }
+        let axis = (0..N)
+            .find(|&axis| step[axis] != 0)
+            .expect("Shift needs a non zero step");
+        let tail_before = self.tail();
+
+        add(&mut self.head.pos, step, ticks);
+        for knot in self.knots.iter_mut() {
+            add(&mut knot.pos, step, ticks);
+        }
+
+        self.tail_visited
+            .insert_segment(tail_before, axis, step[axis] * ticks as i64);
+    }
+}
+
+fn add<const N: usize>(pos: &mut Pos<N>, step: &Pos<N>, times: u32) {
+    for axis in 0..N {
+        pos[axis] += step[axis] * times as i64;
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::render::{knot_label, project, Bounds, Frame};
+use crate::Pos;
+
+/// What a single knot did during the simulation.
+pub struct KnotStats<const N: usize> {
+    /// how many ticks the knot ended on each cell (the starting position included)
+    pub visits: HashMap<Pos<N>, u64>,
+    /// ticks on which the knot actually moved
+    pub moves: u64,
+    /// furthest (manhattan) distance from the origin
+    pub max_distance: i64,
+    pub min: Pos<N>,
+    pub max: Pos<N>,
+    last: Pos<N>,
+}
+
+impl<const N: usize> KnotStats<N> {
+    pub fn new() -> Self {
+        return Self {
+            visits: HashMap::from([([0; N], 1)]),
+            moves: 0,
+            max_distance: 0,
+            min: [0; N],
+            max: [0; N],
+            last: [0; N],
+        };
+    }
+
+    pub fn record(&mut self, pos: Pos<N>) {
+        if pos != self.last {
+            self.moves += 1;
+            self.last = pos;
+        }
+        *self.visits.entry(pos).or_insert(0) += 1;
+        self.max_distance = self.max_distance.max(pos.iter().map(|d| d.abs()).sum());
+        for (axis, d) in pos.iter().enumerate() {
+            self.min[axis] = self.min[axis].min(*d);
+            self.max[axis] = self.max[axis].max(*d);
+        }
+    }
+
+    pub fn most_visits(&self) -> u64 {
+        return self.visits.values().copied().max().unwrap_or(0);
+    }
+}
+
+impl<const N: usize> Default for KnotStats<N> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// Stats for the head (index 0) and every knot after it.
+pub struct RopeStats<const N: usize> {
+    pub knots: Vec<KnotStats<N>>,
+}
+
+impl<const N: usize> RopeStats<N> {
+    pub fn new(knots_count: usize) -> Self {
+        return Self {
+            knots: (0..=knots_count).map(|_| KnotStats::new()).collect(),
+        };
+    }
+
+    pub fn label(&self, index: usize) -> String {
+        if index == 0 {
+            return String::from("H");
+        }
+        return knot_label(index - 1, self.knots.len() - 1).to_string();
+    }
+
+    pub fn print_summary(&self) {
+        println!(
+            "{:<5} {:>10} {:>10} {:>10} {:>10}  bounding box",
+            "knot", "moves", "cells", "max visits", "max dist"
+        );
+        for (i, knot) in self.knots.iter().enumerate() {
+            println!(
+                "{:<5} {:>10} {:>10} {:>10} {:>10}  {:?}..{:?}",
+                self.label(i),
+                knot.moves,
+                knot.visits.len(),
+                knot.most_visits(),
+                knot.max_distance,
+                knot.min,
+                knot.max
+            );
+        }
+    }
+
+    /// `knot,<axes...>,visits` rows, cells sorted for stable diffs.
+    pub fn write_csv(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        let axes = ["x", "y", "z", "w"];
+        let header = (0..N)
+            .map(|axis| match axes.get(axis) {
+                Some(name) => name.to_string(),
+                None => format!("axis_{}", axis),
+            })
+            .collect::<Vec<String>>();
+        writeln!(writer, "knot,{},visits", header.join(","))?;
+
+        for (i, knot) in self.knots.iter().enumerate() {
+            let mut cells = knot.visits.iter().collect::<Vec<_>>();
+            cells.sort();
+            for (pos, visits) in cells {
+                let coords = pos.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+                writeln!(writer, "{},{},{}", self.label(i), coords.join(","), visits)?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+const HEAT: [char; 8] = [':', '-', '=', '+', '*', '#', '%', '@'];
+
+/// Visits of a knot on the x/y plane, `.` for cells never visited and `:` to `@` from the
+/// least to the most visited ones.
+pub fn render_heatmap<const N: usize>(stats: &KnotStats<N>, title: String) -> Frame {
+    let mut projected = HashMap::<(i64, i64), u64>::new();
+    for (pos, visits) in &stats.visits {
+        *projected.entry(project(pos)).or_insert(0) += visits;
+    }
+    let most = projected.values().copied().max().unwrap_or(1);
+
+    let mut bounds = Bounds::around((0, 0));
+    for coord in projected.keys() {
+        bounds.include(*coord);
+    }
+
+    let width = bounds.width();
+    let mut cells = vec!['.'; width * bounds.height()];
+    for ((x, y), visits) in projected {
+        let level = ((visits * HEAT.len() as u64).div_ceil(most) as usize).clamp(1, HEAT.len());
+        let row = (bounds.max_y - y) as usize;
+        let col = (x - bounds.min_x) as usize;
+        cells[row * width + col] = HEAT[level - 1];
+    }
+
+    return Frame {
+        title: Some(title),
+        width,
+        height: bounds.height(),
+        cells,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::*;
+
+    #[test]
+    fn test_knot_stats() {
+        let mut stats = KnotStats::<2>::new();
+        for pos in [[1, 0], [1, 0], [1, 1], [0, 1], [0, 0], [-1, 0]] {
+            stats.record(pos);
+        }
+
+        assert_eq!(stats.moves, 5);
+        assert_eq!(stats.visits.len(), 5);
+        assert_eq!(stats.visits[&[1, 0]], 2);
+        assert_eq!(stats.visits[&[0, 0]], 2);
+        assert_eq!(stats.max_distance, 2);
+        assert_eq!((stats.min, stats.max), ([-1, 0], [1, 1]));
+
+        let heatmap = render_heatmap(&stats, String::from("H"));
+        assert_eq!(heatmap.to_string(), "== H ==\n\n.++\n+@@\n");
+    }
+}
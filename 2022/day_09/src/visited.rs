@@ -0,0 +1,159 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::Pos;
+
+/// Cells visited by a knot. Single cells go in a set, long straight runs are kept as
+/// inclusive ranges on axis aligned lines so huge moves do not need a cell per step.
+pub struct VisitedCells<const N: usize> {
+    cells: HashSet<Pos<N>>,
+    /// `(axis, position with that axis zeroed)` -> sorted and merged ranges on the axis
+    lines: HashMap<(usize, Pos<N>), Vec<(i64, i64)>>,
+}
+
+impl<const N: usize> Default for VisitedCells<N> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<const N: usize> VisitedCells<N> {
+    pub fn new() -> Self {
+        return Self {
+            cells: HashSet::new(),
+            lines: HashMap::new(),
+        };
+    }
+
+    pub fn insert(&mut self, pos: Pos<N>) {
+        if !self.on_a_line(&pos) {
+            self.cells.insert(pos);
+        }
+    }
+
+    /// Visits `from` and the next `count` cells moving one cell at a time on `axis`
+    /// (backwards when `count` is negative).
+    pub fn insert_segment(&mut self, from: Pos<N>, axis: usize, count: i64) {
+        let start = from[axis];
+        let range = if count < 0 {
+            (start + count, start)
+        } else {
+            (start, start + count)
+        };
+
+        let ranges = self.lines.entry(line_key(&from, axis)).or_default();
+        ranges.push(range);
+        ranges.sort();
+
+        let mut merged = Vec::<(i64, i64)>::with_capacity(ranges.len());
+        for &(from, to) in ranges.iter() {
+            match merged.last_mut() {
+                // touching ranges are merged too, they cover contiguous cells
+                Some(last) if from <= last.1 + 1 => last.1 = last.1.max(to),
+                _ => merged.push((from, to)),
+            }
+        }
+        *ranges = merged;
+    }
+
+    pub fn contains(&self, pos: &Pos<N>) -> bool {
+        return self.cells.contains(pos) || self.on_a_line(pos);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.cells.is_empty() && self.lines.is_empty();
+    }
+
+    /// Number of distinct cells visited.
+    pub fn len(&self) -> usize {
+        let single = self.cells.iter().filter(|pos| !self.on_a_line(pos)).count();
+        let on_lines: i64 = self
+            .lines
+            .values()
+            .flat_map(|ranges| ranges.iter().map(|(from, to)| to - from + 1))
+            .sum();
+
+        // cells where lines of different axes cross were counted once per line
+        let mut crossings = HashSet::<Pos<N>>::new();
+        for (axis_a, base_a) in self.lines.keys() {
+            for (axis_b, base_b) in self.lines.keys() {
+                if axis_a >= axis_b {
+                    continue;
+                }
+                let lines_meet = (0..N)
+                    .filter(|axis| axis != axis_a && axis != axis_b)
+                    .all(|axis| base_a[axis] == base_b[axis]);
+                if !lines_meet {
+                    continue;
+                }
+                let mut cell = *base_a;
+                cell[*axis_a] = base_b[*axis_a];
+                if self.covering_lines(&cell) > 1 {
+                    crossings.insert(cell);
+                }
+            }
+        }
+        let counted_twice: usize = crossings
+            .iter()
+            .map(|cell| self.covering_lines(cell) - 1)
+            .sum();
+
+        return single + on_lines as usize - counted_twice;
+    }
+
+    /// Cells visited outside the lines (some may also be on a line).
+    pub fn cells(&self) -> impl Iterator<Item = &Pos<N>> {
+        return self.cells.iter();
+    }
+
+    /// `(axis, position with that axis zeroed, from, to)` of every visited run.
+    pub fn segments(&self) -> impl Iterator<Item = (usize, Pos<N>, i64, i64)> + '_ {
+        return self.lines.iter().flat_map(|((axis, base), ranges)| {
+            return ranges.iter().map(|(from, to)| (*axis, *base, *from, *to));
+        });
+    }
+
+    fn on_a_line(&self, pos: &Pos<N>) -> bool {
+        return !self.lines.is_empty() && self.covering_lines(pos) > 0;
+    }
+
+    fn covering_lines(&self, pos: &Pos<N>) -> usize {
+        return (0..N)
+            .filter(|&axis| match self.lines.get(&line_key(pos, axis)) {
+                Some(ranges) => in_ranges(ranges, pos[axis]),
+                None => false,
+            })
+            .count();
+    }
+}
+
+fn line_key<const N: usize>(pos: &Pos<N>, axis: usize) -> (usize, Pos<N>) {
+    let mut base = *pos;
+    base[axis] = 0;
+    return (axis, base);
+}
+
+fn in_ranges(ranges: &[(i64, i64)], value: i64) -> bool {
+    let idx = ranges.partition_point(|(from, _)| *from <= value);
+    return idx > 0 && ranges[idx - 1].1 >= value;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visited::*;
+
+    #[test]
+    fn test_segments_and_cells_are_counted_once() {
+        let mut visited = VisitedCells::<2>::new();
+        visited.insert([0, 0]);
+        visited.insert([5, 0]);
+        visited.insert([5, 7]);
+        visited.insert_segment([0, 0], 0, 10); // (0,0)..(10,0)
+        visited.insert_segment([12, 0], 0, -2); // (10,0)..(12,0)
+        visited.insert_segment([5, -3], 1, 6); // (5,-3)..(5,3), crosses at (5,0)
+        visited.insert([5, 2]);
+
+        assert_eq!(visited.len(), 13 + 7 - 1 + 1);
+        assert!(visited.contains(&[11, 0]));
+        assert!(!visited.contains(&[11, 1]));
+    }
+}
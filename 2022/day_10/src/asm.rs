@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::costs::CostTable;
+use crate::{Instruction, Register};
+use utils::io::yield_lines_trimmed;
+
+/// Macros calling macros deeper than this are taken as endless recursion.
+const MAX_MACRO_DEPTH: usize = 32;
+
+/// Assembled program, remembering where every instruction came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    /// source line (1 based) of every instruction, the macro call for expanded ones
+    pub lines: Vec<usize>,
+    /// label -> index of the instruction following it
+    pub labels: HashMap<String, usize>,
+}
+
+impl Program {
+    /// Program without a source, as if written one instruction per line.
+    pub fn new(instructions: Vec<Instruction>) -> Self {
+        return Self {
+            lines: (1..=instructions.len()).collect(),
+            instructions,
+            labels: HashMap::new(),
+        };
+    }
+
+    /// Assembles a file, panicking on errors.
+    pub fn load(input_file: &str) -> Self {
+        let source = yield_lines_trimmed(input_file)
+            .collect::<Vec<String>>()
+            .join("\n");
+        return match assemble(&source) {
+            Ok(program) => program,
+            Err(error) => panic!("Could not assemble {}, {}", input_file, error),
+        };
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "line {}: {}", self.line, self.message);
+    }
+}
+
+struct Macro {
+    params: Vec<String>,
+    body: Vec<String>,
+}
+
+struct Assembler {
+    program: Program,
+    macros: HashMap<String, Macro>,
+    /// `(instruction index, label, line)` of jumps to labels, resolved once every label is known
+    jumps_to_labels: Vec<(usize, String, usize)>,
+}
+
+/// Assembles one instruction per line, on top of the puzzle input format it understands:
+/// - comments starting with `;`
+/// - labels, `name:` alone or in front of an instruction, jumps can use them as offsets
+/// - macros, defined between `.macro <name> <params...>` and `.endm` and called like
+///   instructions, `$<param>` in the body being replaced by the argument
+pub fn assemble(source: &str) -> Result<Program, AsmError> {
+    let mut assembler = Assembler {
+        program: Program {
+            instructions: Vec::new(),
+            lines: Vec::new(),
+            labels: HashMap::new(),
+        },
+        macros: HashMap::new(),
+        jumps_to_labels: Vec::new(),
+    };
+
+    let mut defining: Option<(usize, String, Macro)> = None;
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let text = strip_comment(line);
+        let words = text.split_whitespace().collect::<Vec<&str>>();
+
+        if let Some((start, name, mut definition)) = defining.take() {
+            match words.first() {
+                Some(&".endm") => {
+                    assembler.macros.insert(name, definition);
+                }
+                Some(&".macro") => {
+                    return Err(AsmError {
+                        line: line_number,
+                        message: format!("Macro '{}' (line {}) is not closed", name, start),
+                    });
+                }
+                _ => {
+                    definition.body.push(text.to_string());
+                    defining = Some((start, name, definition));
+                }
+            }
+            continue;
+        }
+
+        match words.as_slice() {
+            [] => {}
+            [".macro", name, params @ ..] => {
+                if !is_identifier(name) || params.iter().any(|param| !is_identifier(param)) {
+                    return Err(AsmError {
+                        line: line_number,
+                        message: format!("Invalid macro definition '{}'", text),
+                    });
+                }
+                let definition = Macro {
+                    params: params.iter().map(|param| param.to_string()).collect(),
+                    body: Vec::new(),
+                };
+                defining = Some((line_number, name.to_string(), definition));
+            }
+            [".endm"] => {
+                return Err(AsmError {
+                    line: line_number,
+                    message: String::from(".endm outside of a macro"),
+                });
+            }
+            _ => assembler.statement(text, line_number, 0)?,
+        }
+    }
+
+    if let Some((start, name, _)) = defining {
+        return Err(AsmError {
+            line: start,
+            message: format!("Macro '{}' is never closed with .endm", name),
+        });
+    }
+
+    let mut program = assembler.program;
+    for (index, label, line) in assembler.jumps_to_labels {
+        let Some(&target) = program.labels.get(&label) else {
+            return Err(AsmError {
+                line,
+                message: format!("Unknown label '{}'", label),
+            });
+        };
+        let to = target as i32 - index as i32;
+        match &mut program.instructions[index] {
+            Instruction::Jmp { offset } | Instruction::Jnz { offset, .. } => *offset = to,
+            _ => unreachable!("Only jumps go to labels"),
+        }
+    }
+    return Ok(program);
+}
+
+impl Assembler {
+    /// Label, instruction or macro call found on `line`.
+    fn statement(&mut self, text: &str, line: usize, depth: usize) -> Result<(), AsmError> {
+        let mut text = text.trim();
+        if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if !is_identifier(label) {
+                return Err(AsmError {
+                    line,
+                    message: format!("Invalid label '{}'", label),
+                });
+            }
+            let index = self.program.instructions.len();
+            if self
+                .program
+                .labels
+                .insert(label.to_string(), index)
+                .is_some()
+            {
+                return Err(AsmError {
+                    line,
+                    message: format!("Label '{}' defined twice", label),
+                });
+            }
+            text = rest.trim();
+        }
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(instruction) = Instruction::from_text(text) {
+            self.program.instructions.push(instruction);
+            self.program.lines.push(line);
+            return Ok(());
+        }
+
+        let words = text.split_whitespace().collect::<Vec<&str>>();
+        let jump = match words.as_slice() {
+            ["jmp", label] if is_identifier(label) => Some((Instruction::Jmp { offset: 0 }, label)),
+            ["jnz", register, label] if is_identifier(label) => {
+                Register::parse(register).map(|register| {
+                    (
+                        Instruction::Jnz {
+                            register,
+                            offset: 0,
+                        },
+                        label,
+                    )
+                })
+            }
+            _ => None,
+        };
+        if let Some((instruction, label)) = jump {
+            let index = self.program.instructions.len();
+            self.jumps_to_labels.push((index, label.to_string(), line));
+            self.program.instructions.push(instruction);
+            self.program.lines.push(line);
+            return Ok(());
+        }
+
+        let Some(definition) = self.macros.get(words[0]) else {
+            return Err(AsmError {
+                line,
+                message: format!("Unknown instruction or macro '{}'", text),
+            });
+        };
+        let args = &words[1..];
+        if args.len() != definition.params.len() {
+            return Err(AsmError {
+                line,
+                message: format!(
+                    "Macro '{}' takes {} arguments, got {}",
+                    words[0],
+                    definition.params.len(),
+                    args.len()
+                ),
+            });
+        }
+        if depth >= MAX_MACRO_DEPTH {
+            return Err(AsmError {
+                line,
+                message: format!("Macro '{}' expands too deep", words[0]),
+            });
+        }
+
+        // longest parameters first so `$ab` is not replaced by the value of `$a`
+        let mut substitutions = definition.params.iter().zip(args).collect::<Vec<_>>();
+        substitutions.sort_by_key(|(param, _)| std::cmp::Reverse(param.len()));
+        let body = definition
+            .body
+            .iter()
+            .map(|body_line| {
+                let mut expanded = body_line.clone();
+                for (param, arg) in &substitutions {
+                    expanded = expanded.replace(&format!("${}", param), arg);
+                }
+                return expanded;
+            })
+            .collect::<Vec<String>>();
+
+        for body_line in body {
+            self.statement(&body_line, line, depth + 1)?;
+        }
+        return Ok(());
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    return match line.split_once(';') {
+        Some((code, _)) => code.trim(),
+        None => line.trim(),
+    };
+}
+
+fn is_identifier(word: &str) -> bool {
+    return !word.is_empty()
+        && !word.starts_with(|c: char| c.is_ascii_digit())
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+}
+
+/// Prints a program back as source the assembler reads, with labels and comments telling
+/// where every instruction starts and what the X register is during and after it
+/// when instructions take the cycles in `costs`.
+/// Past the first jump the run can not be followed statically anymore, the comments then
+/// only give the index of every instruction (and the target of jumps).
+pub fn disassemble(program: &Program, costs: &CostTable) -> String {
+    let mut labels = program
+        .labels
+        .iter()
+        .map(|(label, index)| (*index, label.as_str()))
+        .collect::<Vec<_>>();
+    labels.sort();
+    let mut labels = labels.into_iter().peekable();
+
+    let mut out = String::new();
+    let mut registers = crate::cpu::Cpu::new(Vec::new()).registers;
+    let mut cycle = 1;
+    let mut straight = true;
+    for (index, instruction) in program.instructions.iter().enumerate() {
+        while let Some((_, label)) = labels.next_if(|(at, _)| *at == index) {
+            out.push_str(&format!("{}:\n", label));
+        }
+
+        let comment =
+            if let Instruction::Jmp { offset } | Instruction::Jnz { offset, .. } = instruction {
+                straight = false;
+                match usize::try_from(index as i64 + *offset as i64) {
+                    Ok(target) if target < program.instructions.len() => format!("-> {}", target),
+                    _ => String::from("-> end"),
+                }
+            } else if straight {
+                let last_cycle = cycle + costs.cycles_for(instruction) - 1;
+                let cycles = if last_cycle == cycle {
+                    format!("cycle {}", cycle)
+                } else {
+                    format!("cycles {}-{}", cycle, last_cycle)
+                };
+                let during = registers[Register::X.index()];
+                instruction.execute(&mut registers, index);
+                let x = registers[Register::X.index()];
+                cycle = last_cycle + 1;
+                if during == x {
+                    format!("{}, X {}", cycles, x)
+                } else {
+                    format!("{}, X {} -> {}", cycles, during, x)
+                }
+            } else {
+                String::new()
+            };
+
+        let line = format!(
+            "    {:<12}; {:>4}: {}",
+            instruction.to_string(),
+            index,
+            comment
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    for (_, label) in labels {
+        out.push_str(&format!("{}:\n", label));
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asm::*;
+
+    #[test]
+    fn test_assemble_and_disassemble() {
+        let source = "\
+            ; moves the sprite around
+            .macro jump by
+                addx $by
+                noop
+            .endm
+            start: noop
+            jump 5     ; two instructions
+            back:
+            jump -5
+            end:
+        ";
+        let program = assemble(source).unwrap();
+        assert_eq!(
+            program.instructions,
+            vec![
+                Instruction::Noop,
+                Instruction::Add {
+                    register: Register::X,
+                    to_add: 5
+                },
+                Instruction::Noop,
+                Instruction::Add {
+                    register: Register::X,
+                    to_add: -5
+                },
+                Instruction::Noop,
+            ]
+        );
+        assert_eq!(program.lines, vec![6, 7, 7, 9, 9]);
+        assert_eq!(program.labels["back"], 3);
+        assert_eq!(program.labels["end"], 5);
+
+        let listing = disassemble(&program, &CostTable::PUZZLE);
+        assert!(listing.contains("back:\n    addx -5     ;    3: cycles 5-6, X 6 -> 1\n"));
+        let reassembled = assemble(&listing).unwrap();
+        assert_eq!(reassembled.instructions, program.instructions);
+        assert_eq!(reassembled.labels, program.labels);
+        let slow_add = CostTable {
+            add: 3,
+            ..CostTable::PUZZLE
+        };
+        let listing = disassemble(&program, &slow_add);
+        assert!(listing.contains("back:\n    addx -5     ;    3: cycles 6-8, X 6 -> 1\n"));
+
+        let error = assemble("noop\n.macro loop\nloop\n.endm\nloop\n").unwrap_err();
+        assert_eq!(error.line, 5);
+        assert_eq!(assemble("addx").unwrap_err().line, 1);
+        assert!(Instruction::from_text("adé 1").is_none());
+        assert!(Instruction::from_text("muléx 2").is_none());
+        assert_eq!(assemble("noop\naddé 1").unwrap_err().line, 2);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::asm::Program;
+use crate::cpu::Cpu;
+use crate::crt::Crt;
+use crate::{Instruction, Register};
+
+/// First pixel no program can draw (e.g. the sprite always covers the first two pixels).
+#[derive(Debug, PartialEq)]
+pub struct Undrawable {
+    pub row: usize,
+    pub column: usize,
+}
+
+/// Compiles an image (rows of `#` for lit pixels) into an `addx`/`noop` program that draws it
+/// on `crt`, using as few `addx` as possible.
+///
+/// The register only changes at the end of an `addx`, so the program is a chain of runs of
+/// cycles with a steady register, every run but the last at least two cycles long (`noop`s
+/// then the `addx` moving to the next run). The first run starts with the register at 1.
+pub fn compile_image<S: AsRef<str>>(image: &[S], crt: &Crt) -> Result<Program, Undrawable> {
+    if image.len() != crt.height || image.iter().any(|row| row.as_ref().len() > crt.width) {
+        panic!(
+            "Image does not fit a {}x{} screen, rows should be at most {} pixels",
+            crt.width, crt.height, crt.width
+        );
+    }
+    let lit = image
+        .iter()
+        .flat_map(|row| {
+            let row = row.as_ref().as_bytes();
+            return (0..crt.width).map(move |column| row.get(column) == Some(&b'#'));
+        })
+        .collect::<Vec<bool>>();
+    let pixels = lit.len();
+    let drawable = |pixel: usize, x: i32| crt.sprite_covers(pixel % crt.width, x) == lit[pixel];
+
+    // beyond these the sprite is off the screen either way
+    let lowest_x = -(crt.sprite_width as i32) - 1;
+    let highest_x = (crt.width + crt.sprite_width) as i32;
+
+    // fewest runs to draw the pixels before each one, with the run that got there
+    let mut runs_to = vec![usize::MAX; pixels + 1];
+    let mut came_from = vec![None::<(usize, i32)>; pixels + 1];
+    runs_to[0] = 0;
+    let mut furthest = 0;
+    for start in 0..pixels {
+        if runs_to[start] == usize::MAX {
+            continue;
+        }
+        let candidates = if start == 0 {
+            Cpu::START_X..=Cpu::START_X
+        } else {
+            lowest_x..=highest_x
+        };
+        for x in candidates {
+            let mut end = start;
+            while end < pixels && drawable(end, x) {
+                end += 1;
+                furthest = furthest.max(end);
+                let moves_on = end == pixels || end - start >= 2;
+                if moves_on && runs_to[start] + 1 < runs_to[end] {
+                    runs_to[end] = runs_to[start] + 1;
+                    came_from[end] = Some((start, x));
+                }
+            }
+        }
+    }
+
+    if runs_to[pixels] == usize::MAX {
+        return Err(Undrawable {
+            row: furthest / crt.width,
+            column: furthest % crt.width,
+        });
+    }
+
+    let mut runs = Vec::new();
+    let mut end = pixels;
+    while let Some((start, x)) = came_from[end] {
+        runs.push((start, end, x));
+        end = start;
+    }
+    runs.reverse();
+
+    let mut instructions = Vec::new();
+    for (i, &(start, end, x)) in runs.iter().enumerate() {
+        match runs.get(i + 1) {
+            Some(&(_, _, next_x)) => {
+                instructions.extend((0..end - start - 2).map(|_| Instruction::Noop));
+                instructions.push(Instruction::Add {
+                    register: Register::X,
+                    to_add: next_x - x,
+                });
+            }
+            None => instructions.extend((start..end).map(|_| Instruction::Noop)),
+        }
+    }
+    return Ok(Program::new(instructions));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asm::{assemble, disassemble};
+    use crate::compiler::*;
+    use crate::costs::CostTable;
+    use utils::ocr::{draw_letters, read_letters};
+
+    fn draw(program: &Program) -> Crt {
+        let mut crt = Crt::puzzle();
+        Cpu::new(program.instructions.clone()).run(&mut [&mut crt]);
+        return crt;
+    }
+
+    #[test]
+    fn test_compile_word_round_trip() {
+        let image = draw_letters("BEFUZZLE");
+        let program = compile_image(&image, &Crt::puzzle()).unwrap();
+        let crt = draw(&program);
+        assert_eq!(crt.rows(), image);
+        assert_eq!(read_letters(&crt.rows()), Ok(String::from("BEFUZZLE")));
+
+        let listing = disassemble(&program, &CostTable::PUZZLE);
+        assert_eq!(draw(&assemble(&listing).unwrap()).rows(), image);
+    }
+
+    #[test]
+    fn test_undrawable_image() {
+        // the register starts at 1 so the first two pixels are always lit
+        let image = draw_letters("HELLO");
+        assert_eq!(
+            compile_image(&image, &Crt::new(25, 6, 3)),
+            Err(Undrawable { row: 0, column: 1 })
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{Instruction, Opcode};
+use utils::io::yield_lines_trimmed;
+
+/// Cycles taken by every kind of instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostTable {
+    pub noop: usize,
+    pub add: usize,
+    pub mul: usize,
+    pub jmp: usize,
+    pub jnz: usize,
+}
+
+impl Default for CostTable {
+    fn default() -> Self {
+        return CostTable::PUZZLE;
+    }
+}
+
+impl CostTable {
+    /// `noop` and `addx` as in the puzzle, the extended instructions made up on top.
+    pub const PUZZLE: CostTable = CostTable {
+        noop: 1,
+        add: 2,
+        mul: 2,
+        jmp: 1,
+        jnz: 1,
+    };
+
+    /// Costs on top of the puzzle ones, one `<instruction> = <cycles>` per line
+    /// (`#` starts a comment), e.g. `mul = 4`.
+    pub fn load(config_file: &str) -> Self {
+        let mut costs = CostTable::PUZZLE;
+        for (i, line) in yield_lines_trimmed(config_file).enumerate() {
+            let line = match line.split_once('#') {
+                Some((setting, _)) => setting.trim().to_string(),
+                None => line,
+            };
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((name, cycles)) = line.split_once('=') else {
+                panic!(
+                    "{}:{}: expected '<instruction> = <cycles>'",
+                    config_file,
+                    i + 1
+                );
+            };
+            let cycles = match cycles.trim().parse::<usize>() {
+                Ok(cycles) if cycles > 0 => cycles,
+                _ => panic!(
+                    "{}:{}: invalid cycles '{}'",
+                    config_file,
+                    i + 1,
+                    cycles.trim()
+                ),
+            };
+            let cost = match name.trim() {
+                "noop" => &mut costs.noop,
+                "add" => &mut costs.add,
+                "mul" => &mut costs.mul,
+                "jmp" => &mut costs.jmp,
+                "jnz" => &mut costs.jnz,
+                name => panic!("{}:{}: unknown instruction '{}'", config_file, i + 1, name),
+            };
+            *cost = cycles;
+        }
+        return costs;
+    }
+
+    pub fn cycles_for(&self, instruction: &Instruction) -> usize {
+        return match instruction.opcode() {
+            Opcode::Noop => self.noop,
+            Opcode::Add => self.add,
+            Opcode::Mul => self.mul,
+            Opcode::Jmp => self.jmp,
+            Opcode::Jnz => self.jnz,
+        };
+    }
+}
@@ -0,0 +1,184 @@
+use crate::asm::Program;
+use crate::costs::CostTable;
+use crate::{Instruction, Register, Registers};
+
+/// Gets called by the `Cpu` at the end of every cycle.
+pub trait CycleObserver {
+    /// `cycle` is 1 based, `during` is the X register while the cycle runs
+    /// and `after` once the cycle (and maybe an instruction) is done.
+    fn on_cycle(&mut self, cycle: usize, during: i32, after: i32);
+
+    /// Called right before `on_cycle` with the instruction running during the cycle.
+    fn on_instruction(&mut self, _ip: usize, _instruction: &Instruction) {}
+}
+
+/// Everything that changes while running, enough to rewind a `Cpu`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snapshot {
+    pub registers: Registers,
+    pub cycle: usize,
+    pub ip: usize,
+    pub elapsed: usize,
+}
+
+pub struct Cpu {
+    pub program: Vec<Instruction>,
+    pub costs: CostTable,
+    pub registers: Registers,
+    /// cycles done so far
+    pub cycle: usize,
+    /// index of the instruction running
+    pub ip: usize,
+    /// cycles already spent on the instruction running
+    elapsed: usize,
+}
+
+impl Cpu {
+    pub const START_X: i32 = 1;
+
+    pub fn new(program: Vec<Instruction>) -> Self {
+        return Self::with_costs(program, CostTable::PUZZLE);
+    }
+
+    pub fn with_costs(program: Vec<Instruction>, costs: CostTable) -> Self {
+        let mut registers = [0; crate::REGISTERS];
+        registers[Register::X.index()] = Self::START_X;
+        return Self {
+            program,
+            costs,
+            registers,
+            cycle: 0,
+            ip: 0,
+            elapsed: 0,
+        };
+    }
+
+    pub fn load(input_file: &str) -> Self {
+        return Self::new(Program::load(input_file).instructions);
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        return Snapshot {
+            registers: self.registers,
+            cycle: self.cycle,
+            ip: self.ip,
+            elapsed: self.elapsed,
+        };
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.registers = snapshot.registers;
+        self.cycle = snapshot.cycle;
+        self.ip = snapshot.ip;
+        self.elapsed = snapshot.elapsed;
+    }
+
+    pub fn x(&self) -> i32 {
+        return self.registers[Register::X.index()];
+    }
+
+    /// Cycles the instruction running takes.
+    pub fn cost(&self) -> usize {
+        return self.costs.cycles_for(&self.program[self.ip]);
+    }
+
+    /// Cycles already spent on the instruction running.
+    pub fn elapsed(&self) -> usize {
+        return self.elapsed;
+    }
+
+    pub fn is_done(&self) -> bool {
+        return self.ip >= self.program.len();
+    }
+
+    /// Runs a single cycle, returns false (and does nothing) once the program is over.
+    pub fn tick(&mut self, observers: &mut [&mut dyn CycleObserver]) -> bool {
+        if self.is_done() {
+            return false;
+        }
+
+        for observer in observers.iter_mut() {
+            observer.on_instruction(self.ip, &self.program[self.ip]);
+        }
+        let during = self.x();
+        self.cycle += 1;
+        self.elapsed += 1;
+        if self.elapsed >= self.cost() {
+            let instruction = &self.program[self.ip];
+            // jumping before the start ends the program like jumping past the end
+            self.ip = instruction
+                .execute(&mut self.registers, self.ip)
+                .unwrap_or(self.program.len());
+            self.elapsed = 0;
+        }
+
+        let after = self.x();
+        for observer in observers.iter_mut() {
+            observer.on_cycle(self.cycle, during, after);
+        }
+        return true;
+    }
+
+    /// Runs to the end of the program, which never comes for some programs with jumps.
+    pub fn run(&mut self, observers: &mut [&mut dyn CycleObserver]) {
+        while self.tick(observers) {}
+    }
+
+    /// Runs at most up to `last_cycle`, returns if the program ended.
+    pub fn run_until(
+        &mut self,
+        observers: &mut [&mut dyn CycleObserver],
+        last_cycle: usize,
+    ) -> bool {
+        while self.cycle < last_cycle {
+            if !self.tick(observers) {
+                return true;
+            }
+        }
+        return self.is_done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::asm::assemble;
+    use crate::cpu::*;
+    use crate::observers::SignalStrength;
+
+    #[test]
+    fn test_extended_instructions() {
+        let source = "\
+            addy 3
+            loop: addx 2
+            addy -1
+            jnz y loop
+            mulx 3
+            jmp 2
+            addx 100
+        ";
+        let program = assemble(source).unwrap().instructions;
+        assert_eq!(
+            program[3],
+            Instruction::Jnz {
+                register: Register::Y,
+                offset: -2
+            }
+        );
+
+        let mut cpu = Cpu::new(program.clone());
+        let mut signal = SignalStrength::new(&[7, 17]);
+        cpu.run(&mut [&mut signal]);
+        assert_eq!((cpu.x(), cpu.registers[Register::Y.index()]), (21, 0));
+        assert_eq!(cpu.cycle, 2 + 3 * (2 + 2 + 1) + 2 + 1);
+        assert_eq!(signal.sum, 7 * 3 + 17 * 7);
+
+        let costs = CostTable {
+            jnz: 3,
+            ..CostTable::PUZZLE
+        };
+        let mut cpu = Cpu::with_costs(program, costs);
+        assert!(!cpu.run_until(&mut [], 20));
+        assert!(cpu.run_until(&mut [], 100));
+        assert_eq!(cpu.cycle, 2 + 3 * (2 + 2 + 3) + 2 + 1);
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+
+/// Screen drawn one pixel per cycle, left to right and top to bottom, lighting the pixel
+/// when the sprite (centered on the register) covers it.
+pub struct Crt {
+    pub width: usize,
+    pub height: usize,
+    pub sprite_width: usize,
+    pub pixels: Vec<bool>,
+}
+
+impl Crt {
+    pub const PUZZLE_WIDTH: usize = 40;
+    pub const PUZZLE_HEIGHT: usize = 6;
+    pub const PUZZLE_SPRITE_WIDTH: usize = 3;
+
+    pub fn new(width: usize, height: usize, sprite_width: usize) -> Self {
+        if width == 0 || height == 0 || sprite_width == 0 {
+            panic!(
+                "Invalid CRT {}x{} with sprite width {}",
+                width, height, sprite_width
+            );
+        }
+        return Self {
+            width,
+            height,
+            sprite_width,
+            pixels: vec![false; width * height],
+        };
+    }
+
+    pub fn puzzle() -> Self {
+        return Self::new(
+            Self::PUZZLE_WIDTH,
+            Self::PUZZLE_HEIGHT,
+            Self::PUZZLE_SPRITE_WIDTH,
+        );
+    }
+
+    /// Draws the pixel of `cycle` (1 based), cycles past the last pixel wrap to the top.
+    /// Returns if the pixel got lit.
+    pub fn draw(&mut self, cycle: usize, register: i32) -> bool {
+        let idx = (cycle - 1) % self.pixels.len();
+        self.pixels[idx] = self.sprite_covers(idx % self.width, register);
+        return self.pixels[idx];
+    }
+
+    pub fn sprite_covers(&self, column: usize, register: i32) -> bool {
+        let sprite_start = register as i64 - (self.sprite_width as i64 - 1) / 2;
+        let sprite_end = sprite_start + self.sprite_width as i64 - 1;
+        return sprite_start <= column as i64 && column as i64 <= sprite_end;
+    }
+
+    pub fn rows(&self) -> Vec<String> {
+        return self
+            .pixels
+            .chunks(self.width)
+            .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+            .collect();
+    }
+}
+
+impl fmt::Display for Crt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            writeln!(f, "{}", row)?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crt::*;
+
+    #[test]
+    fn test_sprite_width() {
+        let mut crt = Crt::new(5, 1, 1);
+        for cycle in 1..=5 {
+            crt.draw(cycle, 2);
+        }
+        assert_eq!(crt.rows(), vec!["..#.."]);
+
+        let mut crt = Crt::new(5, 1, 4);
+        for cycle in 1..=5 {
+            crt.draw(cycle, 2);
+        }
+        assert_eq!(crt.rows(), vec![".####"]);
+    }
+}
@@ -0,0 +1,372 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::asm::{assemble, AsmError};
+use crate::cpu::{Cpu, Snapshot};
+use crate::{Register, Registers};
+use utils::io::yield_lines_trimmed;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    pub fn parse(symbol: &str) -> Option<Self> {
+        return match symbol {
+            "<" => Some(Comparison::Less),
+            "<=" => Some(Comparison::LessOrEqual),
+            "==" | "=" => Some(Comparison::Equal),
+            "!=" => Some(Comparison::NotEqual),
+            ">=" => Some(Comparison::GreaterOrEqual),
+            ">" => Some(Comparison::Greater),
+            _ => None,
+        };
+    }
+
+    pub fn holds(&self, left: i32, right: i32) -> bool {
+        return match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Greater => left > right,
+        };
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        return match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">",
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakpoint {
+    /// stops right before the cycle runs
+    Cycle(usize),
+    /// stops right before the instruction at that index starts
+    Instruction(usize),
+    /// stops when the register starts satisfying the comparison
+    Register {
+        register: Register,
+        comparison: Comparison,
+        value: i32,
+    },
+}
+
+impl Breakpoint {
+    /// `cycle <n>`, `ip <n>` or `<register> <comparison> <value>`, e.g. `X > 20`.
+    pub fn parse(args: &[&str]) -> Option<Self> {
+        return match args {
+            ["cycle", cycle] => cycle.parse::<usize>().ok().map(Breakpoint::Cycle),
+            ["ip", index] => index.parse::<usize>().ok().map(Breakpoint::Instruction),
+            [register, comparison, value] => Some(Breakpoint::Register {
+                register: Register::parse(register)?,
+                comparison: Comparison::parse(comparison)?,
+                value: value.parse::<i32>().ok()?,
+            }),
+            _ => None,
+        };
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Breakpoint::Cycle(cycle) => write!(f, "cycle {}", cycle),
+            Breakpoint::Instruction(index) => write!(f, "ip {}", index),
+            Breakpoint::Register {
+                register,
+                comparison,
+                value,
+            } => write!(f, "{} {} {}", register.name(), comparison.symbol(), value),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Step(usize),
+    Back(usize),
+    Continue,
+    Print,
+    Break(Breakpoint),
+    Delete(usize),
+    Breakpoints,
+    Help,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Option<Self> {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let count = |arg: Option<&&str>| match arg {
+            Some(count) => count.parse::<usize>().ok(),
+            None => Some(1),
+        };
+        return match words.first().copied() {
+            Some("step" | "s") => count(words.get(1)).map(Command::Step),
+            Some("back" | "bs") => count(words.get(1)).map(Command::Back),
+            Some("continue" | "c") => Some(Command::Continue),
+            Some("print" | "p") => Some(Command::Print),
+            Some("break" | "b") => Breakpoint::parse(&words[1..]).map(Command::Break),
+            Some("delete" | "d") => words.get(1)?.parse::<usize>().ok().map(Command::Delete),
+            Some("breaks" | "info") => Some(Command::Breakpoints),
+            Some("help" | "h") => Some(Command::Help),
+            Some("quit" | "q") => Some(Command::Quit),
+            _ => None,
+        };
+    }
+}
+
+const HELP: &str = "\
+step|s [n]       run n cycles (default 1)
+back|bs [n]      rewind n cycles (default 1)
+continue|c       run until a breakpoint or the end of the program
+print|p          show the registers and the current source line
+break|b cycle <n> | ip <n> | <register> <op> <value>
+                 add a breakpoint (op is one of < <= == != >= >)
+breaks|info      list breakpoints
+delete|d <n>     remove breakpoint n
+quit|q           leave the debugger";
+
+/// Steps through a program, every cycle run is recorded so it can be rewound.
+pub struct Debugger {
+    pub cpu: Cpu,
+    source: Vec<String>,
+    /// source line (1 based) of every instruction
+    lines: Vec<usize>,
+    pub breakpoints: Vec<Breakpoint>,
+    history: Vec<Snapshot>,
+    /// cycle the last `resume` stopped at
+    stopped_at: Option<usize>,
+}
+
+impl Debugger {
+    pub fn new(source: &str) -> Result<Self, AsmError> {
+        let program = assemble(source)?;
+        return Ok(Self {
+            cpu: Cpu::new(program.instructions),
+            source: source.lines().map(|line| line.trim().to_string()).collect(),
+            lines: program.lines,
+            breakpoints: Vec::new(),
+            history: Vec::new(),
+            stopped_at: None,
+        });
+    }
+
+    pub fn load(input_file: &str) -> Self {
+        let source = yield_lines_trimmed(input_file)
+            .collect::<Vec<String>>()
+            .join("\n");
+        return match Self::new(&source) {
+            Ok(debugger) => debugger,
+            Err(error) => panic!("Could not assemble {}, {}", input_file, error),
+        };
+    }
+
+    /// Runs a single cycle, false if the program is over.
+    pub fn step(&mut self) -> bool {
+        let before = self.cpu.snapshot();
+        if !self.cpu.tick(&mut []) {
+            return false;
+        }
+        self.history.push(before);
+        return true;
+    }
+
+    /// Undoes the last cycle, false when back at the start.
+    pub fn back(&mut self) -> bool {
+        return match self.history.pop() {
+            Some(snapshot) => {
+                self.cpu.restore(&snapshot);
+                true
+            }
+            None => false,
+        };
+    }
+
+    /// Runs until a breakpoint is hit (returned) or the program is over.
+    /// Breakpoints on the next cycle or instruction may already hold before any step (like
+    /// `break cycle 1` at the start), unless it stopped right there last time.
+    pub fn resume(&mut self) -> Option<Breakpoint> {
+        let mut before = self.cpu.registers;
+        let mut hit = None;
+        if self.stopped_at != Some(self.cpu.cycle) && !self.cpu.is_done() {
+            hit = self.breakpoints.iter().find(|bp| self.hits(bp, &before));
+        }
+        while hit.is_none() {
+            before = self.cpu.registers;
+            if !self.step() {
+                return None;
+            }
+            hit = self.breakpoints.iter().find(|bp| self.hits(bp, &before));
+        }
+        self.stopped_at = Some(self.cpu.cycle);
+        return hit.copied();
+    }
+
+    fn hits(&self, breakpoint: &Breakpoint, before: &Registers) -> bool {
+        return match breakpoint {
+            Breakpoint::Cycle(cycle) => !self.cpu.is_done() && self.cpu.cycle + 1 == *cycle,
+            Breakpoint::Instruction(index) => self.cpu.ip == *index && self.cpu.elapsed() == 0,
+            Breakpoint::Register {
+                register,
+                comparison,
+                value,
+            } => {
+                let index = register.index();
+                comparison.holds(self.cpu.registers[index], *value)
+                    && !comparison.holds(before[index], *value)
+            }
+        };
+    }
+
+    /// Registers and the source line of the instruction running next.
+    pub fn describe(&self) -> String {
+        let cpu = &self.cpu;
+        if cpu.is_done() {
+            return format!(
+                "program done after cycle {}, {}",
+                cpu.cycle,
+                describe_registers(&cpu.registers)
+            );
+        }
+        let line_number = self.lines[cpu.ip];
+        let line = &self.source[line_number - 1];
+        return format!(
+            "next cycle {}, {}\n{:>4} | {}    (ip {}, cycle {}/{})",
+            cpu.cycle + 1,
+            describe_registers(&cpu.registers),
+            line_number,
+            line,
+            cpu.ip,
+            cpu.elapsed() + 1,
+            cpu.cost()
+        );
+    }
+
+    /// Runs a command, returns false once asked to quit.
+    pub fn execute(&mut self, command: Command, output: &mut impl Write) -> io::Result<bool> {
+        match command {
+            Command::Step(count) => {
+                let steps = (0..count).take_while(|_| self.step()).count();
+                if steps < count {
+                    writeln!(output, "program done")?;
+                }
+                writeln!(output, "{}", self.describe())?;
+            }
+            Command::Back(count) => {
+                let steps = (0..count).take_while(|_| self.back()).count();
+                if steps < count {
+                    writeln!(output, "at the start of the program")?;
+                }
+                writeln!(output, "{}", self.describe())?;
+            }
+            Command::Continue => {
+                if let Some(hit) = self.resume() {
+                    writeln!(output, "breakpoint {}", hit)?;
+                }
+                writeln!(output, "{}", self.describe())?;
+            }
+            Command::Print => writeln!(output, "{}", self.describe())?,
+            Command::Break(breakpoint) => {
+                self.breakpoints.push(breakpoint);
+                writeln!(
+                    output,
+                    "breakpoint {}: {}",
+                    self.breakpoints.len(),
+                    breakpoint
+                )?;
+            }
+            Command::Delete(number) => {
+                if number == 0 || number > self.breakpoints.len() {
+                    writeln!(output, "no breakpoint {}", number)?;
+                } else {
+                    self.breakpoints.remove(number - 1);
+                }
+            }
+            Command::Breakpoints => {
+                for (i, breakpoint) in self.breakpoints.iter().enumerate() {
+                    writeln!(output, "breakpoint {}: {}", i + 1, breakpoint)?;
+                }
+            }
+            Command::Help => writeln!(output, "{}", HELP)?,
+            Command::Quit => return Ok(false),
+        }
+        return Ok(true);
+    }
+
+    /// Reads commands line by line until `quit` or the end of `input`.
+    pub fn repl(&mut self, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "{}", self.describe())?;
+        write!(output, "(debug) ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                match Command::parse(&line) {
+                    Some(command) => {
+                        if !self.execute(command, output)? {
+                            return Ok(());
+                        }
+                    }
+                    None => writeln!(output, "unknown command '{}', try help", line.trim())?,
+                }
+            }
+            write!(output, "(debug) ")?;
+            output.flush()?;
+        }
+        return Ok(());
+    }
+}
+
+/// X and every other register that is not 0.
+fn describe_registers(registers: &Registers) -> String {
+    return Register::ALL
+        .iter()
+        .filter(|register| **register == Register::X || registers[register.index()] != 0)
+        .map(|register| format!("{} = {}", register.name(), registers[register.index()]))
+        .collect::<Vec<String>>()
+        .join(", ");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debugger::*;
+
+    #[test]
+    fn test_breakpoints_and_rewind() {
+        let mut debugger = Debugger::new("noop\naddx 3\naddx -5\naddx 30").unwrap();
+
+        let script = "break cycle 3\nbreak ip 2\nb X > 20\nc\nc\nc\nbs 2\np\nc\nq\nstep\n";
+        let mut output = Vec::new();
+        debugger.repl(script.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("breakpoint cycle 3\nnext cycle 3, X = 1\n   2 | addx 3"));
+        assert!(output.contains("breakpoint ip 2\nnext cycle 4, X = 4\n   3 | addx -5"));
+        assert!(output.contains("breakpoint X > 20\nprogram done after cycle 7, X = 29"));
+        assert!(output.contains("next cycle 6, X = -1\n   4 | addx 30    (ip 3, cycle 1/2)"));
+        assert_eq!(debugger.cpu.cycle, 7);
+
+        let mut debugger = Debugger::new("noop\naddx 3").unwrap();
+        debugger.breakpoints = vec![Breakpoint::Cycle(1), Breakpoint::Instruction(0)];
+        assert_eq!(debugger.resume(), Some(Breakpoint::Cycle(1)));
+        assert_eq!(debugger.cpu.cycle, 0);
+        assert_eq!(debugger.resume(), None);
+        assert_eq!(debugger.cpu.cycle, 3);
+    }
+}
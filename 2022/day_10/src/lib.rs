@@ -1,39 +1,182 @@
-#[derive(Debug)]
+use std::fmt;
+
+use costs::CostTable;
+
+pub mod asm;
+pub mod compiler;
+pub mod costs;
+pub mod cpu;
+pub mod crt;
+pub mod debugger;
+pub mod observers;
+pub mod timeline;
+pub mod trace;
+
+pub const REGISTERS: usize = 4;
+pub type Registers = [i32; REGISTERS];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Register {
+    X,
+    Y,
+    Z,
+    W,
+}
+
+impl Register {
+    pub const ALL: [Register; REGISTERS] = [Register::X, Register::Y, Register::Z, Register::W];
+
+    /// `x` or `X` and so on.
+    pub fn parse(name: &str) -> Option<Self> {
+        return Register::ALL
+            .into_iter()
+            .find(|register| register.name().eq_ignore_ascii_case(name));
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Register::X => "X",
+            Register::Y => "Y",
+            Register::Z => "Z",
+            Register::W => "W",
+        };
+    }
+
+    pub fn index(&self) -> usize {
+        return *self as usize;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Opcode {
+    Noop,
+    Add,
+    Mul,
+    Jmp,
+    Jnz,
+}
+
+/// Registers in instructions are written in lower case, e.g. `addx 3`, `muly -2` or `jnz z -4`.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
     Noop,
-    Add { to_add: i32 },
+    /// `add<register> <value>`
+    Add {
+        register: Register,
+        to_add: i32,
+    },
+    /// `mul<register> <value>`
+    Mul {
+        register: Register,
+        factor: i32,
+    },
+    /// `jmp <offset>`, relative to the jump itself
+    Jmp {
+        offset: i32,
+    },
+    /// `jnz <register> <offset>`, jumps unless the register is 0
+    Jnz {
+        register: Register,
+        offset: i32,
+    },
 }
 
 impl Instruction {
     pub fn parse(line: String) -> Self {
-        let mut split = line.split(' ');
-        if let Some(instruction) = split.next() {
-            match instruction {
-                "addx" => {
-                    if let Some(to_add_str) = split.next() {
-                        let to_add = to_add_str.parse::<i32>().expect("number to add");
-                        return Instruction::Add { to_add };
-                    }
+        return match Instruction::from_text(&line) {
+            Some(instruction) => instruction,
+            None => panic!("Could not read instruction from line {}", line),
+        };
+    }
+
+    /// Same as `parse` but `None` for anything that is not an instruction.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let words = text.split_whitespace().collect::<Vec<&str>>();
+        let number = |word: &str| word.parse::<i32>().ok();
+        return match words.as_slice() {
+            ["noop"] => Some(Instruction::Noop),
+            ["jmp", offset] => Some(Instruction::Jmp {
+                offset: number(offset)?,
+            }),
+            ["jnz", register, offset] => Some(Instruction::Jnz {
+                register: Register::parse(register)?,
+                offset: number(offset)?,
+            }),
+            [op, value] => {
+                if let Some(register) = op.strip_prefix("add") {
+                    return Some(Instruction::Add {
+                        register: Register::parse(register)?,
+                        to_add: number(value)?,
+                    });
                 }
-                "noop" => return Instruction::Noop,
-                _ => {}
-            };
-        }
-        panic!("Could not read instruction from line {}", line);
+                let register = op.strip_prefix("mul")?;
+                Some(Instruction::Mul {
+                    register: Register::parse(register)?,
+                    factor: number(value)?,
+                })
+            }
+            _ => None,
+        };
     }
 
+    pub fn opcode(&self) -> Opcode {
+        return match self {
+            Instruction::Noop => Opcode::Noop,
+            Instruction::Add { .. } => Opcode::Add,
+            Instruction::Mul { .. } => Opcode::Mul,
+            Instruction::Jmp { .. } => Opcode::Jmp,
+            Instruction::Jnz { .. } => Opcode::Jnz,
+        };
+    }
+
+    /// Cycles taken with the puzzle costs.
     pub fn cycles_for(&self) -> usize {
+        return CostTable::PUZZLE.cycles_for(self);
+    }
+
+    pub fn is_jump(&self) -> bool {
+        return matches!(self, Instruction::Jmp { .. } | Instruction::Jnz { .. });
+    }
+
+    /// Applies the instruction to the registers, returns the index of the next one
+    /// or `None` when jumping before the start of the program.
+    /// Overflows wrap around, a looping program could reach them.
+    pub fn execute(&self, registers: &mut Registers, ip: usize) -> Option<usize> {
+        let jump = |offset: &i32| usize::try_from(ip as i64 + *offset as i64).ok();
         return match self {
-            Instruction::Noop => 1,
-            Instruction::Add { to_add: _ } => 2,
+            Instruction::Noop => Some(ip + 1),
+            Instruction::Add { register, to_add } => {
+                let value = &mut registers[register.index()];
+                *value = value.wrapping_add(*to_add);
+                Some(ip + 1)
+            }
+            Instruction::Mul { register, factor } => {
+                let value = &mut registers[register.index()];
+                *value = value.wrapping_mul(*factor);
+                Some(ip + 1)
+            }
+            Instruction::Jmp { offset } => jump(offset),
+            Instruction::Jnz { register, offset } => {
+                if registers[register.index()] != 0 {
+                    jump(offset)
+                } else {
+                    Some(ip + 1)
+                }
+            }
         };
     }
+}
 
-    pub fn apply(&self, register: &mut i32) {
-        match self {
-            Instruction::Noop => {}
-            Instruction::Add { to_add } => {
-                *register += to_add;
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lower = |register: &Register| register.name().to_lowercase();
+        return match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Add { register, to_add } => write!(f, "add{} {}", lower(register), to_add),
+            Instruction::Mul { register, factor } => write!(f, "mul{} {}", lower(register), factor),
+            Instruction::Jmp { offset } => write!(f, "jmp {}", offset),
+            Instruction::Jnz { register, offset } => {
+                write!(f, "jnz {} {}", lower(register), offset)
             }
         };
     }
@@ -1,60 +1,235 @@
-use std::collections::HashMap;
+use std::fs::File;
+use std::io::{stdin, stdout, BufWriter, Write};
 
-use day_10::*;
-use utils::io::yield_lines_trimmed;
+use day_10::asm::{disassemble, Program};
+use day_10::compiler::{compile_image, Undrawable};
+use day_10::costs::CostTable;
+use day_10::cpu::{Cpu, CycleObserver};
+use day_10::crt::Crt;
+use day_10::debugger::Debugger;
+use day_10::observers::{SignalStrength, Trace};
+use day_10::timeline::{Timeline, TimelineRecorder};
+use day_10::trace::{diff_traces, read_trace, TraceFormat, TraceWriter, FIELDS};
+use utils::io::{flag_value, yield_lines_trimmed};
+use utils::ocr::{draw_letters, read_letters};
 
-type ImportantCycle = usize;
-type RegisterValue = i32;
+/// Programs with jumps may loop forever.
+const DEFAULT_MAX_CYCLES: usize = 10_000_000;
 
 fn main() {
-    let (input_file, verbose) = utils::io::parse_args();
-    let part_one = solve(&input_file, verbose);
+    let (input_file, verbose, mode, mode_args) = utils::io::parse_args_with_mode();
+    match mode.as_deref() {
+        None => {}
+        Some("debug") => {
+            let mut debugger = Debugger::load(&input_file);
+            debugger.cpu.costs = parse_costs_flag(&mode_args);
+            debugger
+                .repl(stdin().lock(), &mut stdout())
+                .expect("Could not talk to the terminal");
+            return;
+        }
+        Some("disasm") => {
+            let costs = parse_costs_flag(&mode_args);
+            print!("{}", disassemble(&Program::load(&input_file), &costs));
+            return;
+        }
+        Some("compile") => {
+            compile(&input_file, &mode_args);
+            return;
+        }
+        Some("trace") => {
+            let format = match flag_value(&mode_args, "format") {
+                Some(name) => TraceFormat::parse(name).expect("Trace format is csv or json"),
+                None => TraceFormat::Csv,
+            };
+            let writer: Box<dyn Write> = match flag_value(&mode_args, "out") {
+                Some(path) => Box::new(BufWriter::new(
+                    File::create(path).expect("Could not create the trace file"),
+                )),
+                None => Box::new(stdout().lock()),
+            };
+            let mut trace = TraceWriter::new(writer, format, parse_crt_flags(&mode_args));
+            run_with_flags(&input_file, &mode_args, &mut [&mut trace]);
+            return;
+        }
+        Some("trace-diff") => {
+            trace_diff(&input_file, &mode_args);
+            return;
+        }
+        Some(mode) => {
+            eprintln!(
+                "Unknown mode '{}' (available: debug, disasm, compile, trace, trace-diff)",
+                mode
+            );
+            std::process::exit(1);
+        }
+    }
+
+    let mut recorder = TimelineRecorder::new();
+    let mut part_two = parse_crt_flags(&mode_args);
+    let mut trace = Trace;
+    let mut observers: Vec<&mut dyn CycleObserver> = vec![&mut recorder, &mut part_two];
+    if verbose {
+        observers.push(&mut trace);
+    }
+    run_with_flags(&input_file, &mode_args, &mut observers);
+
+    let part_one = recorder
+        .timeline
+        .signal_strength(&SignalStrength::PUZZLE_CYCLES);
     println!("Part one: {:?}", part_one);
-    // let part_two = solve(&input_file, 9, verbose);
-    // println!("Part two: {:?}", part_two);
+    match read_letters(&part_two.rows()) {
+        Ok(letters) if !verbose => println!("Part two: {}", letters),
+        Ok(letters) => println!("Part two: {}\n{}", letters, part_two),
+        Err(error) => {
+            println!("Part two:\n{}", part_two);
+            eprint!("{}", error);
+        }
+    }
 }
 
-pub fn solve(input_file: &str, verbose: bool) -> i32 {
-    let mut register: i32 = 1;
-    let mut cycle: usize = 0;
+/// Runs the program in `input_file` with `--costs`, stopping after `--max-cycles`.
+fn run_with_flags(
+    input_file: &str,
+    mode_args: &[String],
+    observers: &mut [&mut dyn CycleObserver],
+) {
+    let mut cpu = Cpu::with_costs(
+        Program::load(input_file).instructions,
+        parse_costs_flag(mode_args),
+    );
+    let last_cycle = match flag_value(mode_args, "max-cycles") {
+        Some(value) => value.parse::<usize>().expect("Invalid --max-cycles!"),
+        None => DEFAULT_MAX_CYCLES,
+    };
+    if !cpu.run_until(observers, last_cycle) {
+        eprintln!(
+            "Program still running after {} cycles, stopped it (see --max-cycles)",
+            last_cycle
+        );
+    }
+}
 
-    let important_cycles = [20, 60, 100, 140, 180, 220];
-    let mut important_cycle_idx = 0;
-    let mut register_values_on_important = HashMap::<ImportantCycle, RegisterValue>::new();
+/// `<trace> trace-diff <other trace> --fields <a,b,...>`, comparing every field by default.
+fn trace_diff(input_file: &str, mode_args: &[String]) {
+    let Some(other_file) = mode_args.first().filter(|arg| !arg.starts_with("--")) else {
+        eprintln!("Usage: <trace> trace-diff <other trace> --fields <a,b,...>");
+        std::process::exit(1);
+    };
+    let fields = match flag_value(mode_args, "fields") {
+        Some(fields) => fields.split(',').collect::<Vec<&str>>(),
+        None => FIELDS.to_vec(),
+    };
+    if let Some(field) = fields.iter().find(|field| !FIELDS.contains(field)) {
+        eprintln!(
+            "Unknown field '{}' (available: {})",
+            field,
+            FIELDS.join(", ")
+        );
+        std::process::exit(1);
+    }
 
-    'instructions: for instruction in yield_lines_trimmed(input_file).map(Instruction::parse) {
-        let cycles_cost = instruction.cycles_for();
+    let left = read_trace(input_file);
+    let right = read_trace(other_file);
+    let Some(divergence) = diff_traces(&left, &right, &fields) else {
+        println!("Traces match on all {} cycles", left.len());
+        return;
+    };
+    println!("Traces diverge on cycle {}", divergence.cycle);
+    println!("{}", FIELDS.join(","));
+    for (file, row) in [
+        (input_file, divergence.left),
+        (other_file, divergence.right),
+    ] {
+        match row {
+            Some(row) => println!("{}    ({})", row.to_csv(), file),
+            None => println!("<trace over>    ({})", file),
+        }
+    }
+}
 
-        for _ in 0..cycles_cost {
-            cycle += 1;
-            if important_cycles[important_cycle_idx] != cycle {
-                continue;
-            }
+/// Prints a program drawing the image in `input_file` (rows of `#` and `.`),
+/// or the letters given with `--text` instead.
+fn compile(input_file: &str, mode_args: &[String]) {
+    let crt = parse_crt_flags(mode_args);
+    let image = match flag_value(mode_args, "text") {
+        Some(text) => draw_letters(text),
+        None => yield_lines_trimmed(input_file).collect(),
+    };
+    match compile_image(&image, &crt) {
+        Ok(program) => print!("{}", disassemble(&program, &parse_costs_flag(mode_args))),
+        Err(Undrawable { row, column }) => {
+            eprintln!(
+                "No program can draw this image, stuck at row {} column {}",
+                row, column
+            );
+            std::process::exit(1);
+        }
+    }
+}
 
-            register_values_on_important.insert(cycle, register);
+/// `--costs <config file>`, see `CostTable::load`.
+fn parse_costs_flag(args: &[String]) -> CostTable {
+    return match flag_value(args, "costs") {
+        Some(config_file) => CostTable::load(config_file),
+        None => CostTable::PUZZLE,
+    };
+}
 
-            important_cycle_idx += 1;
-            if important_cycle_idx >= important_cycles.len() {
-                break 'instructions;
-            }
+/// `--screen <width>x<height>` and `--sprite <width>`, defaulting to the puzzle CRT.
+fn parse_crt_flags(args: &[String]) -> Crt {
+    let (width, height) = match flag_value(args, "screen") {
+        Some(value) => {
+            let (width, height) = value
+                .split_once('x')
+                .expect("Screen size should look like 40x6");
+            (
+                width.parse::<usize>().expect("Invalid screen width!"),
+                height.parse::<usize>().expect("Invalid screen height!"),
+            )
         }
-        instruction.apply(&mut register);
-        if verbose {
-            println!(
-                "{:?} cycles: {}, register: {}",
-                &instruction, cycle, register
-            );
+        None => (Crt::PUZZLE_WIDTH, Crt::PUZZLE_HEIGHT),
+    };
+    let sprite_width = match flag_value(args, "sprite") {
+        Some(value) => value.parse::<usize>().expect("Invalid sprite width!"),
+        None => Crt::PUZZLE_SPRITE_WIDTH,
+    };
+    return Crt::new(width, height, sprite_width);
+}
+
+/// Runs the program once with every observer attached.
+pub fn run(input_file: &str, observers: &mut [&mut dyn CycleObserver]) {
+    let mut cpu = Cpu::load(input_file);
+    cpu.run(observers);
+}
+
+pub fn solve(input_file: &str, verbose: bool) -> i32 {
+    let program = Program::load(input_file).instructions;
+    let timeline = match Timeline::from_program(&program, &CostTable::PUZZLE) {
+        Some(timeline) => timeline,
+        None => {
+            let mut recorder = TimelineRecorder::new();
+            Cpu::new(program).run(&mut [&mut recorder]);
+            recorder.timeline
+        }
+    };
+    if verbose {
+        let cycles = SignalStrength::PUZZLE_CYCLES;
+        for (cycle, value) in cycles.iter().zip(timeline.values_during(&cycles)) {
+            println!("cycle {}: X = {:?}", cycle, value);
         }
     }
+    return timeline.signal_strength(&SignalStrength::PUZZLE_CYCLES);
+}
 
+pub fn solve_part_two(input_file: &str, mut crt: Crt, verbose: bool) -> Crt {
+    let mut observers: Vec<&mut dyn CycleObserver> = vec![&mut crt];
+    let mut trace = Trace;
     if verbose {
-        println!("{:?}", register_values_on_important);
+        observers.push(&mut trace);
     }
-
-    return register_values_on_important
-        .iter()
-        .map(|(&k, &v)| k as i32 * v)
-        .sum();
+    run(input_file, &mut observers);
+    return crt;
 }
 
 #[cfg(test)]
@@ -68,10 +243,17 @@ mod tests {
     }
 
     #[test]
-    #[ignore]
     fn test_part_two() {
-        //let part_two = solve("./data/input.txt", 9, false);
-        //assert_eq!(part_two, 2531);
-        todo!()
+        let part_two = solve_part_two("./data/input.txt", Crt::puzzle(), false);
+        assert_eq!(read_letters(&part_two.rows()), Ok(String::from("BRJLFULP")));
+    }
+
+    #[test]
+    fn test_observers_in_one_run() {
+        let mut signal = SignalStrength::puzzle();
+        let mut crt = Crt::puzzle();
+        run("./data/sample.txt", &mut [&mut signal, &mut crt]);
+        assert_eq!(signal.sum, 13140);
+        assert_eq!(crt.rows()[0], "##..##..##..##..##..##..##..##..##..##..");
     }
 }
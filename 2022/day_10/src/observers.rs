@@ -0,0 +1,50 @@
+use crate::cpu::CycleObserver;
+use crate::crt::Crt;
+
+/// Sums `cycle * register` (during the cycle) over a few important cycles.
+pub struct SignalStrength {
+    pub important_cycles: Vec<usize>,
+    pub sum: i32,
+}
+
+impl SignalStrength {
+    pub const PUZZLE_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
+
+    pub fn new(important_cycles: &[usize]) -> Self {
+        return Self {
+            important_cycles: important_cycles.to_vec(),
+            sum: 0,
+        };
+    }
+
+    pub fn puzzle() -> Self {
+        return Self::new(&Self::PUZZLE_CYCLES);
+    }
+}
+
+impl CycleObserver for SignalStrength {
+    fn on_cycle(&mut self, cycle: usize, during: i32, _after: i32) {
+        if self.important_cycles.contains(&cycle) {
+            self.sum += cycle as i32 * during;
+        }
+    }
+}
+
+impl CycleObserver for Crt {
+    fn on_cycle(&mut self, cycle: usize, during: i32, _after: i32) {
+        self.draw(cycle, during);
+    }
+}
+
+/// Prints every cycle.
+pub struct Trace;
+
+impl CycleObserver for Trace {
+    fn on_cycle(&mut self, cycle: usize, during: i32, after: i32) {
+        if during == after {
+            println!("cycle {}: X = {}", cycle, during);
+        } else {
+            println!("cycle {}: X = {} -> {}", cycle, during, after);
+        }
+    }
+}
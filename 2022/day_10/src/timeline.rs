@@ -0,0 +1,209 @@
+use crate::costs::CostTable;
+use crate::cpu::{Cpu, CycleObserver};
+use crate::{Instruction, Register};
+
+/// Values of the X register over a whole run, answering "X during cycle N" without running
+/// the program again. Only the cycles where X changes are kept, with the running value
+/// (the prefix sums of the changes for `addx` programs) from the next cycle on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    start: i32,
+    /// cycles at the end of which X changes, increasing
+    boundaries: Vec<usize>,
+    /// X from the cycle after the matching boundary on
+    values: Vec<i32>,
+    /// cycles the program ran for
+    pub cycles: usize,
+}
+
+impl Timeline {
+    fn new(start: i32) -> Self {
+        return Self {
+            start,
+            boundaries: Vec::new(),
+            values: Vec::new(),
+            cycles: 0,
+        };
+    }
+
+    /// Builds the timeline straight from the instructions, `None` for programs with jumps
+    /// (run them with a `TimelineRecorder` instead).
+    pub fn from_program(program: &[Instruction], costs: &CostTable) -> Option<Self> {
+        if program.iter().any(|instruction| instruction.is_jump()) {
+            return None;
+        }
+        let mut registers = Cpu::new(Vec::new()).registers;
+        let mut timeline = Self::new(registers[Register::X.index()]);
+        for (ip, instruction) in program.iter().enumerate() {
+            let during = registers[Register::X.index()];
+            instruction.execute(&mut registers, ip);
+            timeline.cycles += costs.cycles_for(instruction);
+            timeline.push(timeline.cycles, during, registers[Register::X.index()]);
+        }
+        return Some(timeline);
+    }
+
+    fn push(&mut self, cycle: usize, during: i32, after: i32) {
+        if during != after {
+            self.boundaries.push(cycle);
+            self.values.push(after);
+        }
+    }
+
+    /// X during `cycle` (1 based), `None` outside of the run.
+    pub fn value_during(&self, cycle: usize) -> Option<i32> {
+        if cycle == 0 || cycle > self.cycles {
+            return None;
+        }
+        let changes = self
+            .boundaries
+            .partition_point(|&boundary| boundary < cycle);
+        return Some(self.value_after_changes(changes));
+    }
+
+    /// `value_during` for every cycle, in the same order. The cycles are looked up sorted
+    /// so every search starts where the previous one ended.
+    pub fn values_during(&self, cycles: &[usize]) -> Vec<Option<i32>> {
+        let mut order = (0..cycles.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|&i| cycles[i]);
+
+        let mut values = vec![None; cycles.len()];
+        let mut changes = 0;
+        for i in order {
+            let cycle = cycles[i];
+            if cycle == 0 || cycle > self.cycles {
+                continue;
+            }
+            changes += self.boundaries[changes..].partition_point(|&boundary| boundary < cycle);
+            values[i] = Some(self.value_after_changes(changes));
+        }
+        return values;
+    }
+
+    fn value_after_changes(&self, changes: usize) -> i32 {
+        return match changes {
+            0 => self.start,
+            _ => self.values[changes - 1],
+        };
+    }
+
+    /// Sum of `cycle * X during the cycle`, cycles outside of the run counting for nothing.
+    pub fn signal_strength(&self, cycles: &[usize]) -> i32 {
+        return cycles
+            .iter()
+            .zip(self.values_during(cycles))
+            .map(|(&cycle, value)| cycle as i32 * value.unwrap_or(0))
+            .sum();
+    }
+}
+
+/// Builds a `Timeline` while the program runs, for programs `Timeline::from_program` can not
+/// follow.
+pub struct TimelineRecorder {
+    pub timeline: Timeline,
+}
+
+impl TimelineRecorder {
+    pub fn new() -> Self {
+        return Self {
+            timeline: Timeline::new(Cpu::START_X),
+        };
+    }
+}
+
+impl Default for TimelineRecorder {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl CycleObserver for TimelineRecorder {
+    fn on_cycle(&mut self, cycle: usize, during: i32, after: i32) {
+        self.timeline.cycles = cycle;
+        self.timeline.push(cycle, during, after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::observers::SignalStrength;
+    use crate::timeline::*;
+    use utils::random::Rng;
+
+    /// X during every cycle, the plain way.
+    fn step_by_step(program: &[Instruction], costs: &CostTable) -> Vec<i32> {
+        struct Values(Vec<i32>);
+        impl CycleObserver for Values {
+            fn on_cycle(&mut self, _cycle: usize, during: i32, _after: i32) {
+                self.0.push(during);
+            }
+        }
+        let mut values = Values(Vec::new());
+        Cpu::with_costs(program.to_vec(), *costs).run(&mut [&mut values]);
+        return values.0;
+    }
+
+    fn random_program(rng: &mut Rng) -> Vec<Instruction> {
+        return (0..rng.between(0, 60))
+            .map(|_| match rng.below(5) {
+                0 => Instruction::Noop,
+                1 => Instruction::Mul {
+                    register: Register::X,
+                    factor: rng.between(0, 4) as i32 - 2,
+                },
+                2 => Instruction::Add {
+                    register: Register::Y,
+                    to_add: 1,
+                },
+                _ => Instruction::Add {
+                    register: Register::X,
+                    to_add: rng.between(0, 40) as i32 - 20,
+                },
+            })
+            .collect();
+    }
+
+    #[test]
+    fn test_fuzz_against_step_by_step() {
+        let mut rng = Rng::new(10);
+        for _ in 0..300 {
+            let program = random_program(&mut rng);
+            let costs = CostTable {
+                noop: rng.between(1, 3),
+                add: rng.between(1, 4),
+                mul: rng.between(1, 4),
+                ..CostTable::PUZZLE
+            };
+            let expected = step_by_step(&program, &costs);
+            let timeline = Timeline::from_program(&program, &costs).unwrap();
+            assert_eq!(timeline.cycles, expected.len());
+
+            let mut recorder = TimelineRecorder::new();
+            Cpu::with_costs(program.clone(), costs).run(&mut [&mut recorder]);
+            assert_eq!(recorder.timeline, timeline);
+
+            for (i, value) in expected.iter().enumerate() {
+                assert_eq!(timeline.value_during(i + 1), Some(*value));
+            }
+            assert_eq!(timeline.value_during(0), None);
+            assert_eq!(timeline.value_during(expected.len() + 1), None);
+
+            let queries = (0..rng.below(30))
+                .map(|_| rng.below(expected.len() + 3))
+                .collect::<Vec<usize>>();
+            let bulk = queries
+                .iter()
+                .map(|&cycle| timeline.value_during(cycle))
+                .collect::<Vec<_>>();
+            assert_eq!(timeline.values_during(&queries), bulk);
+
+            let mut signal = SignalStrength::new(&queries);
+            Cpu::with_costs(program, costs).run(&mut [&mut signal]);
+            // the observer counts repeated cycles once
+            let mut unique = queries.clone();
+            unique.sort();
+            unique.dedup();
+            assert_eq!(timeline.signal_strength(&unique), signal.sum);
+        }
+    }
+}
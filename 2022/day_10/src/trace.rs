@@ -0,0 +1,252 @@
+use std::io::Write;
+
+use crate::cpu::CycleObserver;
+use crate::crt::Crt;
+use crate::Instruction;
+use utils::io::yield_lines_trimmed;
+
+pub const FIELDS: [&str; 6] = ["cycle", "ip", "instruction", "x_during", "x_after", "pixel"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceFormat {
+    Csv,
+    JsonLines,
+}
+
+impl TraceFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        return match name {
+            "csv" => Some(TraceFormat::Csv),
+            "json" | "jsonl" => Some(TraceFormat::JsonLines),
+            _ => None,
+        };
+    }
+}
+
+/// What happened during a single cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceRow {
+    pub cycle: usize,
+    pub ip: usize,
+    pub instruction: String,
+    pub x_during: i32,
+    pub x_after: i32,
+    /// if the CRT pixel drawn on the cycle is lit
+    pub pixel: bool,
+}
+
+impl TraceRow {
+    /// Value of one of the `FIELDS`, as written in the CSV.
+    pub fn field(&self, name: &str) -> String {
+        return match name {
+            "cycle" => self.cycle.to_string(),
+            "ip" => self.ip.to_string(),
+            "instruction" => self.instruction.clone(),
+            "x_during" => self.x_during.to_string(),
+            "x_after" => self.x_after.to_string(),
+            "pixel" => String::from(if self.pixel { "#" } else { "." }),
+            _ => panic!("Unknown trace field '{}'", name),
+        };
+    }
+
+    pub fn to_csv(&self) -> String {
+        return FIELDS.map(|name| self.field(name)).join(",");
+    }
+
+    pub fn to_json(&self) -> String {
+        return format!(
+            "{{\"cycle\":{},\"ip\":{},\"instruction\":\"{}\",\"x_during\":{},\"x_after\":{},\"pixel\":{}}}",
+            self.cycle, self.ip, self.instruction, self.x_during, self.x_after, self.pixel
+        );
+    }
+
+    /// Reads a row written by `to_csv`.
+    pub fn from_csv(line: &str) -> Option<Self> {
+        let values = line.split(',').collect::<Vec<&str>>();
+        if values.len() != FIELDS.len() {
+            return None;
+        }
+        return Some(Self {
+            cycle: values[0].parse().ok()?,
+            ip: values[1].parse().ok()?,
+            instruction: values[2].to_string(),
+            x_during: values[3].parse().ok()?,
+            x_after: values[4].parse().ok()?,
+            pixel: match values[5] {
+                "#" => true,
+                "." => false,
+                _ => return None,
+            },
+        });
+    }
+
+    /// Reads a row written by `to_json`, only knows about the flat objects it writes.
+    pub fn from_json(line: &str) -> Option<Self> {
+        let body = line.trim().strip_prefix('{')?.strip_suffix('}')?;
+        let mut values = Vec::new();
+        for name in FIELDS {
+            let key = format!("\"{}\":", name);
+            let start = body.find(&key)? + key.len();
+            let rest = &body[start..];
+            let value = match rest.strip_prefix('"') {
+                Some(quoted) => &quoted[..quoted.find('"')?],
+                None => rest.split(',').next()?,
+            };
+            values.push(value.trim());
+        }
+        return Some(Self {
+            cycle: values[0].parse().ok()?,
+            ip: values[1].parse().ok()?,
+            instruction: values[2].to_string(),
+            x_during: values[3].parse().ok()?,
+            x_after: values[4].parse().ok()?,
+            pixel: values[5].parse().ok()?,
+        });
+    }
+}
+
+/// Writes a `TraceRow` for every cycle, the pixels following the geometry of `crt`.
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    format: TraceFormat,
+    crt: Crt,
+    ip: usize,
+    instruction: String,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(mut writer: W, format: TraceFormat, crt: Crt) -> Self {
+        if format == TraceFormat::Csv {
+            writeln!(writer, "{}", FIELDS.join(",")).expect("Could not write trace");
+        }
+        return Self {
+            writer,
+            format,
+            crt,
+            ip: 0,
+            instruction: String::new(),
+        };
+    }
+}
+
+impl<W: Write> CycleObserver for TraceWriter<W> {
+    fn on_instruction(&mut self, ip: usize, instruction: &Instruction) {
+        self.ip = ip;
+        self.instruction = instruction.to_string();
+    }
+
+    fn on_cycle(&mut self, cycle: usize, during: i32, after: i32) {
+        let row = TraceRow {
+            cycle,
+            ip: self.ip,
+            instruction: self.instruction.clone(),
+            x_during: during,
+            x_after: after,
+            pixel: self.crt.draw(cycle, during),
+        };
+        let line = match self.format {
+            TraceFormat::Csv => row.to_csv(),
+            TraceFormat::JsonLines => row.to_json(),
+        };
+        writeln!(self.writer, "{}", line).expect("Could not write trace");
+    }
+}
+
+/// Reads a trace in either format, telling them apart by the first line.
+pub fn read_trace(trace_file: &str) -> Vec<TraceRow> {
+    let mut rows = Vec::new();
+    for (i, line) in yield_lines_trimmed(trace_file).enumerate() {
+        if line.is_empty() || (i == 0 && line == FIELDS.join(",")) {
+            continue;
+        }
+        let row = match line.starts_with('{') {
+            true => TraceRow::from_json(&line),
+            false => TraceRow::from_csv(&line),
+        };
+        match row {
+            Some(row) => rows.push(row),
+            None => panic!("{}:{}: not a trace row: {}", trace_file, i + 1, line),
+        }
+    }
+    return rows;
+}
+
+/// First cycle where the traces differ on any of `fields`, `None` on the side
+/// of a trace that is already over.
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    pub cycle: usize,
+    pub left: Option<TraceRow>,
+    pub right: Option<TraceRow>,
+}
+
+pub fn diff_traces(left: &[TraceRow], right: &[TraceRow], fields: &[&str]) -> Option<Divergence> {
+    for i in 0..left.len().max(right.len()) {
+        let (left, right) = (left.get(i), right.get(i));
+        let same = match (left, right) {
+            (Some(left), Some(right)) => fields.iter().all(|f| left.field(f) == right.field(f)),
+            _ => false,
+        };
+        if !same {
+            // traces cut out of a longer run don't start at cycle 1
+            let row = left.or(right).expect("Index within one of the traces");
+            return Some(Divergence {
+                cycle: row.cycle,
+                left: left.cloned(),
+                right: right.cloned(),
+            });
+        }
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::Cpu;
+    use crate::trace::*;
+
+    fn trace(program: Vec<Instruction>, format: TraceFormat) -> Vec<TraceRow> {
+        let mut output = Vec::new();
+        let mut writer = TraceWriter::new(&mut output, format, Crt::puzzle());
+        Cpu::new(program).run(&mut [&mut writer]);
+        return String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .skip(if format == TraceFormat::Csv { 1 } else { 0 })
+            .map(|line| match format {
+                TraceFormat::Csv => TraceRow::from_csv(line).unwrap(),
+                TraceFormat::JsonLines => TraceRow::from_json(line).unwrap(),
+            })
+            .collect();
+    }
+
+    #[test]
+    fn test_trace_formats_and_diff() {
+        let program = ["noop", "addx 3", "addx -5"].map(|l| Instruction::parse(l.to_string()));
+        let csv = trace(program.to_vec(), TraceFormat::Csv);
+        let json = trace(program.to_vec(), TraceFormat::JsonLines);
+        assert_eq!(csv, json);
+        assert_eq!(csv.len(), 5);
+        assert_eq!(csv[2].to_csv(), "3,1,addx 3,1,4,#");
+        assert_eq!(
+            csv[4].to_json(),
+            "{\"cycle\":5,\"ip\":2,\"instruction\":\"addx -5\",\"x_during\":4,\"x_after\":-1,\"pixel\":true}"
+        );
+        assert_eq!(diff_traces(&csv, &json, &FIELDS), None);
+
+        let mut changed = program.to_vec();
+        changed[2] = Instruction::parse(String::from("addx 5"));
+        let other = trace(changed, TraceFormat::Csv);
+        let divergence = diff_traces(&csv, &other, &FIELDS).unwrap();
+        assert_eq!(divergence.cycle, 4);
+        let divergence = diff_traces(&csv[2..], &other[2..], &FIELDS).unwrap();
+        assert_eq!(divergence.cycle, 4);
+        assert_eq!(
+            diff_traces(&csv, &other, &["x_during", "x_after"])
+                .unwrap()
+                .cycle,
+            5
+        );
+        assert_eq!(diff_traces(&csv, &csv[..3], &FIELDS).unwrap().right, None);
+    }
+}
@@ -0,0 +1,168 @@
+/// Shell like pattern on `/` separated paths:
+/// - `*` any characters but `/`, `?` a single one
+/// - `[abc]`, `[a-z]` one of the characters, `[!abc]` any other
+/// - `**` as a whole segment, any number of segments (none included)
+///
+/// Empty segments are ignored so `/a//b/` is the same as `/a/b`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glob {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    AnySegments,
+    Tokens(Vec<Token>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Char(char),
+    AnyChar,
+    AnyChars,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Token {
+    fn matches(&self, c: char) -> bool {
+        return match self {
+            Token::Char(expected) => c == *expected,
+            Token::AnyChar => true,
+            Token::AnyChars => true,
+            Token::Class { negated, ranges } => {
+                ranges.iter().any(|&(from, to)| from <= c && c <= to) != *negated
+            }
+        };
+    }
+}
+
+impl Glob {
+    /// `None` if a `[` class is never closed.
+    pub fn parse(pattern: &str) -> Option<Self> {
+        let mut segments = Vec::new();
+        for segment in pattern.split('/').filter(|segment| !segment.is_empty()) {
+            if segment == "**" {
+                // `**/**` matches the same as `**`
+                if segments.last() != Some(&Segment::AnySegments) {
+                    segments.push(Segment::AnySegments);
+                }
+                continue;
+            }
+            segments.push(Segment::Tokens(Self::parse_tokens(segment)?));
+        }
+        return Some(Self { segments });
+    }
+
+    fn parse_tokens(segment: &str) -> Option<Vec<Token>> {
+        let mut tokens = Vec::new();
+        let mut chars = segment.chars().peekable();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '?' => Token::AnyChar,
+                '*' => Token::AnyChars,
+                '[' => {
+                    let negated = chars.next_if(|&c| c == '!' || c == '^').is_some();
+                    let mut ranges = Vec::new();
+                    // a `]` right at the start is part of the class
+                    let mut first = true;
+                    loop {
+                        let from = chars.next()?;
+                        if from == ']' && !first {
+                            break;
+                        }
+                        first = false;
+                        let to = match chars.next_if_eq(&'-') {
+                            Some(_) => match chars.next()? {
+                                ']' => {
+                                    ranges.push((from, from));
+                                    ranges.push(('-', '-'));
+                                    break;
+                                }
+                                to => to,
+                            },
+                            None => from,
+                        };
+                        ranges.push((from, to));
+                    }
+                    Token::Class { negated, ranges }
+                }
+                c => Token::Char(c),
+            };
+            tokens.push(token);
+        }
+        return Some(tokens);
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        let names = path
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<&str>>();
+        return Self::match_segments(&self.segments, &names);
+    }
+
+    fn match_segments(segments: &[Segment], names: &[&str]) -> bool {
+        return match segments.split_first() {
+            None => names.is_empty(),
+            Some((Segment::AnySegments, rest)) => {
+                (0..=names.len()).any(|skipped| Self::match_segments(rest, &names[skipped..]))
+            }
+            Some((Segment::Tokens(tokens), rest)) => match names.split_first() {
+                Some((name, names)) => {
+                    let chars = name.chars().collect::<Vec<char>>();
+                    Self::match_tokens(tokens, &chars) && Self::match_segments(rest, names)
+                }
+                None => false,
+            },
+        };
+    }
+
+    /// Matches a single segment, `matched[j]` telling if the tokens so far match
+    /// the first `j` characters.
+    fn match_tokens(tokens: &[Token], chars: &[char]) -> bool {
+        let mut matched = vec![false; chars.len() + 1];
+        matched[0] = true;
+        for token in tokens {
+            let mut next = vec![false; chars.len() + 1];
+            for j in 0..=chars.len() {
+                if token == &Token::AnyChars {
+                    // any number of characters after a match
+                    next[j] = matched[j] || (j > 0 && next[j - 1]);
+                } else if j > 0 {
+                    next[j] = matched[j - 1] && token.matches(chars[j - 1]);
+                }
+            }
+            matched = next;
+        }
+        return matched[chars.len()];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::glob::*;
+
+    #[test]
+    fn test_glob() {
+        let glob = Glob::parse("/a/**/*.dat").unwrap();
+        assert!(glob.is_match("/a/x.dat"));
+        assert!(glob.is_match("/a/b/c/x.dat"));
+        assert!(!glob.is_match("/b/x.dat"));
+        assert!(!glob.is_match("/a/x.data"));
+
+        let glob = Glob::parse("/[a-c]?/[!x]*").unwrap();
+        assert!(glob.is_match("/bz/file"));
+        assert!(!glob.is_match("/dz/file"));
+        assert!(!glob.is_match("/b/file"));
+        assert!(!glob.is_match("/bz/xfile"));
+
+        assert!(Glob::parse("**").unwrap().is_match("/"));
+        assert!(Glob::parse("*a*b*").unwrap().is_match("xaybz"));
+        assert!(!Glob::parse("*a*b*").unwrap().is_match("xbya"));
+        assert!(Glob::parse("[]-]").unwrap().is_match("-"));
+        assert_eq!(Glob::parse("/a/[bc"), None);
+    }
+}
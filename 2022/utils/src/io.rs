@@ -14,10 +14,9 @@ pub fn parse_args() -> (String, bool) {
         return (input_file.clone(), false);
     }
 
-    let verbose = match args[2].to_lowercase().as_str() {
-        "true" | "-v" => true,
-        "false" => false,
-        _ => {
+    let verbose = match parse_verbose_flag(&args[2]) {
+        Some(verbose) => verbose,
+        None => {
             eprintln!(
                 "Unexpected verbose flag (use true, false or -v): '{}'",
                 args[2]
@@ -29,6 +28,60 @@ pub fn parse_args() -> (String, bool) {
     return (input_file.clone(), verbose);
 }
 
+/// Like `parse_args` but also accepts a mode (and its arguments) after the optional verbose flag:
+/// `<input_file> <verbose flag?> <mode?> <mode args...>`
+pub fn parse_args_with_mode() -> (String, bool, Option<String>, Vec<String>) {
+    let args = env::args().collect::<Vec<String>>();
+    if args.len() < 2 {
+        eprintln!(
+            "Usage: {} <input_file> <verbose flag?> <mode?> <mode args...>",
+            args[0]
+        );
+        std::process::exit(1);
+    }
+
+    let input_file = args[1].clone();
+    let mut rest = args[2..].to_vec();
+
+    let mut verbose = false;
+    if let Some(flag) = rest.first().and_then(|arg| parse_verbose_flag(arg)) {
+        verbose = flag;
+        rest.remove(0);
+    }
+
+    // `--flags` are mode args, so `<input_file> --threads 4` runs the default mode
+    let mut mode = None;
+    if rest.first().is_some_and(|arg| !arg.starts_with("--")) {
+        mode = Some(rest.remove(0));
+    }
+
+    return (input_file, verbose, mode, rest);
+}
+
+/// Looks for `--<name> <value>` among the mode args.
+pub fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let flag = format!("--{}", name);
+    return args
+        .iter()
+        .position(|arg| *arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|value| value.as_str());
+}
+
+/// Checks if a `--<name>` switch is among the mode args.
+pub fn has_flag(args: &[String], name: &str) -> bool {
+    let flag = format!("--{}", name);
+    return args.contains(&flag);
+}
+
+fn parse_verbose_flag(arg: &str) -> Option<bool> {
+    return match arg.to_lowercase().as_str() {
+        "true" | "-v" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    };
+}
+
 pub fn yield_lines(file_path: &str) -> io::Lines<io::BufReader<File>> {
     let reader = open_read_buffer(file_path);
     return reader.lines();
@@ -1 +1,5 @@
+pub mod glob;
 pub mod io;
+pub mod ocr;
+pub mod pathfinding;
+pub mod random;
@@ -0,0 +1,175 @@
+use std::fmt;
+
+/// Letters of the block font drawn by the puzzles are 4 pixels wide and 6 tall,
+/// with an empty column between them.
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+pub const GLYPH_SPACING: usize = 1;
+
+/// Letters seen in puzzle outputs. `Y` is left out, the puzzles draw it 5 pixels wide
+/// which runs into the spacing column.
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Glyph that is not a letter of the font.
+#[derive(Debug, PartialEq)]
+pub struct UnknownGlyph {
+    /// column of the glyph's first pixel on the screen
+    pub column: usize,
+    pub rows: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum OcrError {
+    /// letters only fit on rows as tall as them
+    WrongHeight {
+        rows: usize,
+    },
+    UnknownGlyphs(Vec<UnknownGlyph>),
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            OcrError::WrongHeight { rows } => writeln!(
+                f,
+                "Letters are {} pixels tall, got {} rows",
+                GLYPH_HEIGHT, rows
+            ),
+            OcrError::UnknownGlyphs(unknown) => {
+                for glyph in unknown {
+                    write!(f, "{}", glyph)?;
+                }
+                Ok(())
+            }
+        };
+    }
+}
+
+impl fmt::Display for UnknownGlyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Unknown glyph at column {}:", self.column)?;
+        for row in &self.rows {
+            writeln!(f, "{}", row)?;
+        }
+        return Ok(());
+    }
+}
+
+/// Reads the letters off `rows` of `#` (lit) and `.` pixels, every other character counting
+/// as unlit. Blank glyphs are read as spaces and trimmed from the ends.
+pub fn read_letters<S: AsRef<str>>(rows: &[S]) -> Result<String, OcrError> {
+    if rows.len() != GLYPH_HEIGHT {
+        return Err(OcrError::WrongHeight { rows: rows.len() });
+    }
+    let pixels = rows
+        .iter()
+        .map(|row| {
+            row.as_ref()
+                .chars()
+                .map(|c| c == '#')
+                .collect::<Vec<bool>>()
+        })
+        .collect::<Vec<_>>();
+    let width = pixels.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let mut text = String::new();
+    let mut unknown = Vec::new();
+    for column in (0..width).step_by(GLYPH_WIDTH + GLYPH_SPACING) {
+        let glyph = pixels
+            .iter()
+            .map(|row| {
+                return (column..column + GLYPH_WIDTH)
+                    .map(|x| if row.get(x) == Some(&true) { '#' } else { '.' })
+                    .collect::<String>();
+            })
+            .collect::<Vec<String>>();
+
+        if glyph.iter().all(|row| !row.contains('#')) {
+            text.push(' ');
+            continue;
+        }
+        match FONT.iter().find(|(_, letter)| *letter == glyph.as_slice()) {
+            Some((letter, _)) => text.push(*letter),
+            None => unknown.push(UnknownGlyph {
+                column,
+                rows: glyph,
+            }),
+        }
+    }
+
+    if !unknown.is_empty() {
+        return Err(OcrError::UnknownGlyphs(unknown));
+    }
+    return Ok(text.trim().to_string());
+}
+
+/// Draws `text` (capital letters of the font and spaces) the way `read_letters` reads it,
+/// every glyph followed by its spacing column.
+pub fn draw_letters(text: &str) -> Vec<String> {
+    let mut rows = vec![String::new(); GLYPH_HEIGHT];
+    for letter in text.chars() {
+        let glyph = match FONT.iter().find(|(c, _)| *c == letter) {
+            Some((_, glyph)) => *glyph,
+            None if letter == ' ' => ["...."; GLYPH_HEIGHT],
+            None => panic!("No glyph for '{}' in the font", letter),
+        };
+        for (row, glyph_row) in rows.iter_mut().zip(glyph) {
+            row.push_str(glyph_row);
+            row.push_str(&".".repeat(GLYPH_SPACING));
+        }
+    }
+    return rows;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ocr::*;
+
+    #[test]
+    fn test_read_letters() {
+        let rows = [
+            "#..#.####.#....#.....##.",
+            "#..#.#....#....#....#..#",
+            "####.###..#....#....#..#",
+            "#..#.#....#....#....#..#",
+            "#..#.#....#....#....#..#",
+            "#..#.####.####.####..##.",
+        ];
+        assert_eq!(read_letters(&rows), Ok(String::from("HELLO")));
+        assert_eq!(draw_letters("HELLO")[5], format!("{}.", rows[5]));
+
+        let mut broken = rows.map(String::from);
+        broken[0].replace_range(5..6, ".");
+        let Err(OcrError::UnknownGlyphs(unknown)) = read_letters(&broken) else {
+            panic!("The broken E should not be read");
+        };
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].column, 5);
+        assert_eq!(
+            read_letters(&rows[..5]),
+            Err(OcrError::WrongHeight { rows: 5 })
+        );
+        assert_eq!(
+            read_letters(&draw_letters("HI JO")),
+            Ok(String::from("HI JO"))
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+pub type GridPos = (usize, usize);
+
+/// Breadth first search from `start` until `is_goal` matches.
+/// Returns the path (both ends included) with the fewest steps.
+pub fn bfs<N, FN, IN, FG>(start: N, mut neighbours: FN, mut is_goal: FG) -> Option<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut parents = HashMap::<N, N>::new();
+    let mut visited = HashSet::<N>::new();
+    let mut queue = VecDeque::<N>::new();
+
+    visited.insert(start.clone());
+    queue.push_back(start);
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some(reconstruct_path(&parents, node));
+        }
+        for next in neighbours(&node) {
+            if visited.insert(next.clone()) {
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    return None;
+}
+
+/// Dijkstra from `start` until `is_goal` matches. `neighbours` yields `(node, step cost)` pairs.
+/// Returns the cheapest path (both ends included) and its total cost.
+pub fn dijkstra<N, FN, IN, FG>(start: N, neighbours: FN, is_goal: FG) -> Option<(Vec<N>, usize)>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, usize)>,
+    FG: FnMut(&N) -> bool,
+{
+    return astar(start, neighbours, |_| 0, is_goal);
+}
+
+/// A* from `start` until `is_goal` matches. `heuristic` must never overestimate the remaining
+/// cost, otherwise the returned path may not be the cheapest one.
+pub fn astar<N, FN, IN, FH, FG>(
+    start: N,
+    mut neighbours: FN,
+    mut heuristic: FH,
+    mut is_goal: FG,
+) -> Option<(Vec<N>, usize)>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, usize)>,
+    FH: FnMut(&N) -> usize,
+    FG: FnMut(&N) -> bool,
+{
+    // nodes are kept on a side vec so the heap does not require `N: Ord`
+    let mut nodes = Vec::<N>::new();
+    let mut best_costs = HashMap::<N, usize>::new();
+    let mut parents = HashMap::<N, N>::new();
+    let mut open = BinaryHeap::<Reverse<(usize, usize, usize)>>::new();
+
+    best_costs.insert(start.clone(), 0);
+    open.push(Reverse((heuristic(&start), 0, nodes.len())));
+    nodes.push(start);
+
+    while let Some(Reverse((_, cost, idx))) = open.pop() {
+        let node = nodes[idx].clone();
+        if best_costs.get(&node).is_some_and(|&best| best < cost) {
+            continue; // stale heap entry
+        }
+        if is_goal(&node) {
+            return Some((reconstruct_path(&parents, node), cost));
+        }
+
+        for (next, step_cost) in neighbours(&node) {
+            let next_cost = cost + step_cost;
+            if best_costs.get(&next).is_some_and(|&best| best <= next_cost) {
+                continue;
+            }
+            best_costs.insert(next.clone(), next_cost);
+            parents.insert(next.clone(), node.clone());
+            open.push(Reverse((
+                next_cost + heuristic(&next),
+                next_cost,
+                nodes.len(),
+            )));
+            nodes.push(next);
+        }
+    }
+    return None;
+}
+
+/// Walks the `parents` links back from `goal`, returning the path in start to goal order.
+pub fn reconstruct_path<N>(parents: &HashMap<N, N>, goal: N) -> Vec<N>
+where
+    N: Eq + Hash + Clone,
+{
+    let mut path = vec![goal];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    return path;
+}
+
+/// Every node reachable from `start` (itself included).
+pub fn flood_fill<N, FN, IN>(start: N, mut neighbours: FN) -> HashSet<N>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut visited = HashSet::<N>::new();
+    let mut stack = vec![start.clone()];
+    visited.insert(start);
+
+    while let Some(node) = stack.pop() {
+        for next in neighbours(&node) {
+            if visited.insert(next.clone()) {
+                stack.push(next);
+            }
+        }
+    }
+    return visited;
+}
+
+/// Up, down, left and right neighbours of `pos` inside a `width` x `height` grid.
+pub fn grid_neighbours(pos: GridPos, width: usize, height: usize) -> Vec<GridPos> {
+    let (row, col) = pos;
+    let mut neighbours = Vec::with_capacity(4);
+    if row > 0 {
+        neighbours.push((row - 1, col));
+    }
+    if row + 1 < height {
+        neighbours.push((row + 1, col));
+    }
+    if col > 0 {
+        neighbours.push((row, col - 1));
+    }
+    if col + 1 < width {
+        neighbours.push((row, col + 1));
+    }
+    return neighbours;
+}
+
+pub fn manhattan(a: GridPos, b: GridPos) -> usize {
+    return a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+}
+
+/// Shortest path on a grid where a step can climb at most `max_climb` above the current cell
+/// (going down is always allowed).
+pub fn grid_climb_path<FH>(
+    width: usize,
+    height: usize,
+    height_at: FH,
+    start: GridPos,
+    goal: GridPos,
+    max_climb: usize,
+) -> Option<Vec<GridPos>>
+where
+    FH: Fn(GridPos) -> usize,
+{
+    let climbable = |pos: &GridPos| {
+        let max_height = height_at(*pos) + max_climb;
+        return grid_neighbours(*pos, width, height)
+            .into_iter()
+            .filter(|next| height_at(*next) <= max_height)
+            .map(|next| (next, 1))
+            .collect::<Vec<_>>();
+    };
+    return astar(
+        start,
+        climbable,
+        |pos| manhattan(*pos, goal),
+        |pos| *pos == goal,
+    )
+    .map(|(path, _)| path);
+}
+
+/// Every grid cell reachable from `start` through cells accepted by `passable`.
+pub fn grid_flood_fill<FP>(
+    width: usize,
+    height: usize,
+    start: GridPos,
+    passable: FP,
+) -> HashSet<GridPos>
+where
+    FP: Fn(GridPos) -> bool,
+{
+    return flood_fill(start, |pos| {
+        return grid_neighbours(*pos, width, height)
+            .into_iter()
+            .filter(|next| passable(*next))
+            .collect::<Vec<_>>();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: [&str; 4] = ["S.#.", ".##.", "...#", "#..G"];
+
+    fn maze_neighbours(pos: &GridPos) -> Vec<GridPos> {
+        return grid_neighbours(*pos, 4, 4)
+            .into_iter()
+            .filter(|(row, col)| MAZE[*row].as_bytes()[*col] != b'#')
+            .collect();
+    }
+
+    #[test]
+    fn test_bfs_and_dijkstra_agree_on_unit_costs() {
+        let goal = (3, 3);
+        let path = bfs((0, 0), maze_neighbours, |pos| *pos == goal).unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&goal));
+
+        let weighted = |pos: &GridPos| maze_neighbours(pos).into_iter().map(|next| (next, 1));
+        let (_, cost) = dijkstra((0, 0), weighted, |pos| *pos == goal).unwrap();
+        assert_eq!(cost, 6);
+        let (_, cost) = astar(
+            (0, 0),
+            weighted,
+            |pos| manhattan(*pos, goal),
+            |pos| *pos == goal,
+        )
+        .unwrap();
+        assert_eq!(cost, 6);
+    }
+
+    #[test]
+    fn test_unreachable_and_flood_fill() {
+        assert_eq!(bfs((0, 0), maze_neighbours, |pos| *pos == (0, 3)), None);
+        assert_eq!(flood_fill((0, 0), maze_neighbours).len(), 9);
+        assert_eq!(flood_fill((0, 3), maze_neighbours).len(), 2);
+    }
+
+    #[test]
+    fn test_climb_path() {
+        let heights = [[0, 1, 2], [5, 5, 3], [9, 5, 4]];
+        let height_at = |(row, col): GridPos| heights[row][col];
+        let path = grid_climb_path(3, 3, height_at, (0, 0), (2, 2), 1).unwrap();
+        assert_eq!(path, vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]);
+        assert_eq!(grid_climb_path(3, 3, height_at, (0, 0), (2, 0), 1), None);
+    }
+}
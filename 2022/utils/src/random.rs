@@ -0,0 +1,50 @@
+/// Small seeded PRNG (xorshift64*) for generating puzzle-like inputs.
+/// Not suited for anything security related.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // zero is a fixed point of xorshift
+        let state = if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        };
+        return Self { state };
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        return self.state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+    }
+
+    /// Uniform-ish value in `[0, bound)`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "Rng bound must be positive");
+        return (self.next_u64() % bound as u64) as usize;
+    }
+
+    /// Uniform-ish value in `[from, to]`.
+    pub fn between(&mut self, from: usize, to: usize) -> usize {
+        return from + self.below(to - from + 1);
+    }
+
+    pub fn chance(&mut self, percent: usize) -> bool {
+        return self.below(100) < percent;
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        return &items[self.below(items.len())];
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}